@@ -156,6 +156,35 @@ impl<T> List<T> {
     }
 }
 
+// Serialized as a sequence from front to back
+// No Iter for this list (see below), so walk the nodes and borrow them one by one
+#[cfg(feature = "serde")]
+impl<T: serde::Serialize> serde::Serialize for List<T> {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        use serde::ser::SerializeSeq;
+
+        let mut seq = serializer.serialize_seq(Some(self.len))?;
+        let mut cur = self.head.clone();
+        while let Some(node) = cur {
+            seq.serialize_element(&node.borrow().val)?;
+            cur = node.borrow().next.clone();
+        }
+        seq.end()
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de, T: serde::Deserialize<'de>> serde::Deserialize<'de> for List<T> {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let vals = <Vec<T> as serde::Deserialize>::deserialize(deserializer)?;
+        let mut list = List::new();
+        for val in vals {
+            list.push_back(val);
+        }
+        Ok(list)
+    }
+}
+
 /* struct Iter<T>(Option<Rc<RefCell<Node<T>>>>);
    With Rc version, we can't define lifetime of Iter
    So we can't get Ref or RefMut of RefCell if we can't define lifetime
@@ -202,4 +231,27 @@ mod tests {
         assert_eq!(iter.next_back(), Some(1));
         assert_eq!(iter.next(), Some(2));
     }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_serde() {
+        use crate::test_serializer::{from_value, to_value, Value};
+
+        let mut list = List::new();
+        list.push_back(2);
+        list.push_back(3);
+        list.push_front(1);
+        let value = to_value(&list).unwrap();
+        assert_eq!(
+            value,
+            Value::Seq(vec![Value::Int(1), Value::Int(2), Value::Int(3)])
+        );
+
+        let mut list: List<i32> = from_value(value).unwrap();
+        assert_eq!(list.len, 3);
+        assert_eq!(list.pop_front(), Some(1));
+        assert_eq!(list.pop_back(), Some(3));
+        assert_eq!(list.pop_front(), Some(2));
+        assert_eq!(list.pop_front(), None);
+    }
 }
@@ -0,0 +1,339 @@
+use super::{ArcK, List, PointerKind, RcK};
+use std::fmt::{self, Debug};
+
+// Persistent deque (Okasaki's banker's deque) made of two persistent lists
+// front holds the first half from the front, back holds the second half from the back
+//
+//     deque: 1 2 3 4 5 6
+//     front: 1 -> 2 -> 3
+//     back:  6 -> 5 -> 4
+//
+// Every operation returns a new version and leaves the old one untouched,
+// versions share the nodes they have in common
+//
+// Neither list may get more than C times longer than the other (+1, so a single element fits).
+// When that breaks, both are rebuilt from the middle, cloning the moved values: O(n).
+// It takes about n / C cheap operations to break the balance again,
+// so every operation is amortized O(1), as long as each version is only extended once.
+// Going back to an old version right before a rebuild and extending it again repeats
+// the rebuild, the bound then only holds per operation in the worst case: O(n)
+
+const C: usize = 3;
+
+pub struct Deque<T, P: PointerKind = RcK> {
+    front: List<T, P>,
+    back: List<T, P>,
+}
+
+impl<T> Deque<T> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl<T> Deque<T, ArcK> {
+    pub fn new_sync() -> Self {
+        Self::default()
+    }
+}
+
+impl<T, P: PointerKind> Deque<T, P> {
+    pub fn len(&self) -> usize {
+        self.front.len() + self.back.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    // One side can only be empty if the other one has at most one element
+    pub fn front(&self) -> Option<&T> {
+        self.front.head().or_else(|| self.back.head())
+    }
+
+    pub fn back(&self) -> Option<&T> {
+        self.back.head().or_else(|| self.front.head())
+    }
+
+    pub fn iter(&self) -> Iter<'_, T, P> {
+        Iter {
+            front: self.front.iter(),
+            back: &self.back,
+            reversed: None,
+        }
+    }
+}
+
+// Only push and pop may rebuild the lists, which clones the values
+impl<T: Clone, P: PointerKind> Deque<T, P> {
+    pub fn push_front(&self, val: T) -> Self {
+        Self::balance(self.front.prepend(val), self.back.clone())
+    }
+
+    pub fn push_back(&self, val: T) -> Self {
+        Self::balance(self.front.clone(), self.back.prepend(val))
+    }
+
+    // None if there is nothing to pop
+    pub fn pop_front(&self) -> Option<Self> {
+        if self.front.is_empty() {
+            // Last element sits in back
+            return self.back.head().map(|_| Self::default());
+        }
+        Some(Self::balance(self.front.tail(), self.back.clone()))
+    }
+
+    pub fn pop_back(&self) -> Option<Self> {
+        if self.back.is_empty() {
+            return self.front.head().map(|_| Self::default());
+        }
+        Some(Self::balance(self.front.clone(), self.back.tail()))
+    }
+
+    fn balance(front: List<T, P>, back: List<T, P>) -> Self {
+        let keep = (front.len() + back.len()) / 2;
+        if front.len() > C * back.len() + 1 {
+            // Keep the first half in front, the rest goes to the end of back
+            let (front, moved) = split(&front, keep);
+            Self {
+                front,
+                back: append_reversed(&back, moved),
+            }
+        } else if back.len() > C * front.len() + 1 {
+            let (back, moved) = split(&back, keep);
+            Self {
+                front: append_reversed(&front, moved),
+                back,
+            }
+        } else {
+            Self { front, back }
+        }
+    }
+}
+
+// First n values as a new list, and the rest, which is shared with list
+fn split<T: Clone, P: PointerKind>(list: &List<T, P>, n: usize) -> (List<T, P>, List<T, P>) {
+    let vals: Vec<_> = list.iter().take(n).cloned().collect();
+    let mut rest = list.clone();
+    for _ in 0..n {
+        rest = rest.tail();
+    }
+    (from_vals(vals, List::default()), rest)
+}
+
+// list followed by the values of rest in reverse, only the nodes of list are rebuilt
+//
+//     list: 6 -> 5, rest: 3 -> 4   =>   6 -> 5 -> 4 -> 3
+fn append_reversed<T: Clone, P: PointerKind>(list: &List<T, P>, rest: List<T, P>) -> List<T, P> {
+    let mut reversed = List::default();
+    for val in rest.iter() {
+        reversed = reversed.prepend(val.clone());
+    }
+    from_vals(list.iter().cloned().collect(), reversed)
+}
+
+// vals in order, in front of tail
+fn from_vals<T, P: PointerKind>(vals: Vec<T>, mut tail: List<T, P>) -> List<T, P> {
+    for val in vals.into_iter().rev() {
+        tail = tail.prepend(val);
+    }
+    tail
+}
+
+impl<T, P: PointerKind> Default for Deque<T, P> {
+    fn default() -> Self {
+        Self {
+            front: List::default(),
+            back: List::default(),
+        }
+    }
+}
+
+// Only bumps the counts of both heads
+impl<T, P: PointerKind> Clone for Deque<T, P> {
+    fn clone(&self) -> Self {
+        Self {
+            front: self.front.clone(),
+            back: self.back.clone(),
+        }
+    }
+}
+
+impl<T: Debug, P: PointerKind> Debug for Deque<T, P> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
+
+// back list runs from the end, so it has to be reversed before it can be handed out
+// That costs one Vec of references as long as back (at most about half the deque),
+// only paid once front runs out, so taking the first few values stays cheap
+pub struct Iter<'a, T, P: PointerKind> {
+    front: super::Iter<'a, T, P>,
+    back: &'a List<T, P>,
+    reversed: Option<Vec<&'a T>>,
+}
+
+impl<'a, T, P: PointerKind> Iterator for Iter<'a, T, P> {
+    type Item = &'a T;
+    fn next(&mut self) -> Option<Self::Item> {
+        if let Some(val) = self.front.next() {
+            return Some(val);
+        }
+        let back = self.back;
+        self.reversed
+            .get_or_insert_with(|| back.iter().collect())
+            .pop()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::VecDeque;
+
+    fn check<T: Debug + PartialEq, P: PointerKind>(deque: &Deque<T, P>, model: &VecDeque<T>) {
+        assert_eq!(deque.len(), model.len());
+        assert_eq!(deque.is_empty(), model.is_empty());
+        assert_eq!(deque.front(), model.front());
+        assert_eq!(deque.back(), model.back());
+        assert!(deque.iter().eq(model.iter()));
+        // Balance holds after every operation
+        assert!(deque.front.len() <= C * deque.back.len() + 1);
+        assert!(deque.back.len() <= C * deque.front.len() + 1);
+    }
+
+    #[test]
+    fn test() {
+        let deque = Deque::new();
+        assert_eq!(deque.front(), None);
+        assert_eq!(deque.back(), None);
+        assert!(deque.pop_front().is_none());
+        assert!(deque.pop_back().is_none());
+
+        let deque = deque.push_back(2).push_back(3).push_front(1);
+        assert_eq!(deque.front(), Some(&1));
+        assert_eq!(deque.back(), Some(&3));
+        assert_eq!(deque.iter().copied().collect::<Vec<_>>(), &[1, 2, 3]);
+        assert_eq!(format!("{:?}", deque), "[1, 2, 3]");
+        // back is only reversed once front runs out
+        let mut iter = deque.iter();
+        assert_eq!(iter.next(), Some(&1));
+        assert!(iter.reversed.is_none());
+        assert_eq!(iter.by_ref().last(), Some(&3));
+        assert!(iter.reversed.is_some());
+
+        let deque = deque.pop_front().unwrap();
+        assert_eq!(deque.front(), Some(&2));
+        let deque = deque.pop_back().unwrap();
+        assert_eq!(deque.front(), Some(&2));
+        assert_eq!(deque.back(), Some(&2));
+        let deque = deque.pop_back().unwrap();
+        assert!(deque.is_empty());
+        assert!(deque.pop_front().is_none());
+    }
+
+    #[test]
+    fn test_one_end() {
+        // Everything pushed at one end and popped from the other goes through rebuilds
+        let mut deque = Deque::new();
+        for i in 0..100 {
+            deque = deque.push_back(i);
+        }
+        for i in 0..100 {
+            assert_eq!(deque.front(), Some(&i));
+            deque = deque.pop_front().unwrap();
+        }
+        assert!(deque.is_empty());
+
+        for i in 0..100 {
+            deque = deque.push_front(i);
+        }
+        for i in 0..100 {
+            assert_eq!(deque.back(), Some(&i));
+            deque = deque.pop_back().unwrap();
+        }
+        assert!(deque.is_empty());
+    }
+
+    #[test]
+    fn test_persistence() {
+        let base = Deque::new().push_back(1).push_back(2);
+        let left = base.push_front(0);
+        let right = base.push_back(3);
+        let popped = right.pop_front().unwrap();
+
+        assert_eq!(base.iter().copied().collect::<Vec<_>>(), &[1, 2]);
+        assert_eq!(left.iter().copied().collect::<Vec<_>>(), &[0, 1, 2]);
+        assert_eq!(right.iter().copied().collect::<Vec<_>>(), &[1, 2, 3]);
+        assert_eq!(popped.iter().copied().collect::<Vec<_>>(), &[2, 3]);
+    }
+
+    #[test]
+    fn test_model() {
+        // Random operations on random old versions, every version must still match its model
+        let mut versions = vec![(Deque::new(), VecDeque::new())];
+        let mut seed = 0x2545_f491_4f6c_dd1du64;
+        let mut rand = move |n: usize| {
+            seed ^= seed << 13;
+            seed ^= seed >> 7;
+            seed ^= seed << 17;
+            (seed % n as u64) as usize
+        };
+
+        for i in 0..5_000 {
+            // Mostly extend the latest version, so it gets long enough to rebuild
+            let pick = if rand(4) == 0 {
+                rand(versions.len())
+            } else {
+                versions.len() - 1
+            };
+            let (deque, model): &(Deque<usize>, VecDeque<usize>) = &versions[pick];
+            let mut model = model.clone();
+            let deque = match rand(5) {
+                0 | 1 => {
+                    model.push_back(i);
+                    deque.push_back(i)
+                }
+                2 => {
+                    model.push_front(i);
+                    deque.push_front(i)
+                }
+                3 => {
+                    let popped = deque.pop_front();
+                    assert_eq!(popped.is_some(), model.pop_front().is_some());
+                    popped.unwrap_or_default()
+                }
+                _ => {
+                    let popped = deque.pop_back();
+                    assert_eq!(popped.is_some(), model.pop_back().is_some());
+                    popped.unwrap_or_default()
+                }
+            };
+            check(&deque, &model);
+            versions.push((deque, model));
+        }
+
+        for (deque, model) in &versions {
+            check(deque, model);
+        }
+    }
+
+    #[test]
+    fn test_threads() {
+        fn assert_send_sync<T: Send + Sync>() {}
+        assert_send_sync::<Deque<i32, ArcK>>();
+
+        let deque = Deque::new_sync().push_back(1).push_back(2);
+        std::thread::scope(|s| {
+            for t in 0..4 {
+                let deque = &deque;
+                s.spawn(move || {
+                    let mine = deque.push_front(t).push_back(t);
+                    assert_eq!(mine.iter().copied().collect::<Vec<_>>(), &[t, 1, 2, t]);
+                });
+            }
+        });
+        assert_eq!(deque.iter().copied().collect::<Vec<_>>(), &[1, 2]);
+    }
+}
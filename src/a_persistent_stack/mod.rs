@@ -1,31 +1,117 @@
+mod deque; // Two persistent lists, one for each end
+mod random_access; // Skew binary trees, O(log n) indexing
+mod stream; // Lazy, memoized tails, can be infinite
+
+use std::cmp::Ordering;
+use std::fmt::{self, Debug};
+use std::hash::{Hash, Hasher};
+use std::ops::Deref;
 use std::rc::Rc;
+use std::sync::Arc;
+
+// Which shared pointer the nodes live in
+// Rc is cheaper, Arc lets versions of the list be shared between threads
+pub trait PointerKind {
+    type Pointer<T>: Deref<Target = T> + Clone;
+
+    fn new<T>(val: T) -> Self::Pointer<T>;
+    // Some only if this is the last strong reference
+    // Unlike try_unwrap, when the last two references are dropped at the same time
+    // exactly one of them gets the value, so Drop can never give up on the rest of the chain
+    fn into_inner<T>(ptr: Self::Pointer<T>) -> Option<T>;
+}
 
 #[derive(Debug)]
-struct Node<T> {
-    val: T,
-    next: Option<Rc<Node<T>>>,
+pub struct RcK;
+
+impl PointerKind for RcK {
+    type Pointer<T> = Rc<T>;
+
+    fn new<T>(val: T) -> Rc<T> {
+        Rc::new(val)
+    }
+
+    fn into_inner<T>(ptr: Rc<T>) -> Option<T> {
+        Rc::into_inner(ptr)
+    }
 }
 
 #[derive(Debug)]
-struct List<T> {
-    head: Option<Rc<Node<T>>>,
+pub struct ArcK;
+
+impl PointerKind for ArcK {
+    type Pointer<T> = Arc<T>;
+
+    fn new<T>(val: T) -> Arc<T> {
+        Arc::new(val)
+    }
+
+    fn into_inner<T>(ptr: Arc<T>) -> Option<T> {
+        Arc::into_inner(ptr)
+    }
+}
+
+// List<T, ArcK> is Send + Sync when T is, since Arc<Node> is
+// List<T, RcK> (the default) stays on one thread, like Rc itself
+struct Node<T, P: PointerKind> {
+    val: T,
+    next: Option<P::Pointer<Node<T, P>>>,
+}
+
+struct List<T, P: PointerKind = RcK> {
+    head: Option<P::Pointer<Node<T, P>>>,
+    // Cached, so len() doesn't walk the list
+    len: usize,
 }
 
 impl<T> List<T> {
     pub fn new() -> Self {
-        Self { head: None }
+        Self::default()
     }
+}
 
-    pub fn prepend(&mut self, val: T) -> List<T> {
+impl<T> List<T, ArcK> {
+    pub fn new_sync() -> Self {
+        Self::default()
+    }
+}
+
+impl<T, P: PointerKind> List<T, P> {
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.head.is_none()
+    }
+
+    // New list with val in front of this one, both share every node of this one
+    pub fn cons(&self, val: T) -> List<T, P> {
         List {
-            head: Some(Rc::new(Node {
+            head: Some(P::new(Node {
                 val,
                 next: self.head.clone(),
             })),
+            len: self.len + 1,
         }
     }
 
-    pub fn tail(&self) -> List<T> {
+    pub fn prepend(&self, val: T) -> List<T, P> {
+        self.cons(val)
+    }
+
+    // Split into head and tail, None if empty
+    pub fn uncons(&self) -> Option<(&T, List<T, P>)> {
+        self.head.as_deref().map(|node| {
+            let tail = List {
+                head: node.next.clone(),
+                len: self.len - 1,
+            };
+            (&node.val, tail)
+        })
+    }
+
+    pub fn tail(&self) -> List<T, P> {
         List {
             // difference between map() and and_then()
             // - and_then()
@@ -35,6 +121,7 @@ impl<T> List<T> {
             // shared feature
             //      - return None if head is None
             head: self.head.as_ref().and_then(|node| node.next.clone()),
+            len: self.len.saturating_sub(1),
         }
     }
 
@@ -42,34 +129,186 @@ impl<T> List<T> {
         self.head.as_deref().map(|node| &node.val)
     }
 
-    pub fn iter(&self) -> Iter<T> {
+    pub fn iter(&self) -> Iter<T, P> {
         Iter {
             next: self.head.as_deref(),
+            len: self.len,
+        }
+    }
+
+    pub fn fold<B, F: FnMut(B, &T) -> B>(&self, init: B, f: F) -> B {
+        self.iter().fold(init, f)
+    }
+
+    pub fn map<U, F: FnMut(&T) -> U>(&self, f: F) -> List<U, P> {
+        self.iter().map(f).collect()
+    }
+
+    // Drops the first n nodes, the rest is shared
+    pub fn skip(&self, n: usize) -> List<T, P> {
+        let mut next = self.head.as_ref();
+        for _ in 0..n.min(self.len) {
+            next = next.and_then(|node| node.next.as_ref());
+        }
+        List {
+            head: next.cloned(),
+            len: self.len.saturating_sub(n),
         }
     }
 }
 
-struct Iter<'a, T> {
-    next: Option<&'a Node<T>>,
+// Values can't be moved out of shared nodes, so anything building new nodes clones them
+impl<T: Clone, P: PointerKind> List<T, P> {
+    pub fn reverse(&self) -> List<T, P> {
+        self.fold(List::default(), |list, val| list.cons(val.clone()))
+    }
+
+    // Copies the nodes of self, other is shared
+    //
+    //     self: 1 -> 2, other: 3 -> 4   =>   1' -> 2' -> 3 -> 4
+    pub fn append(&self, other: &List<T, P>) -> List<T, P> {
+        self.copy_prefix(self.len, other.clone())
+    }
+
+    pub fn filter<F: FnMut(&T) -> bool>(&self, mut f: F) -> List<T, P> {
+        self.iter().filter(|val| f(val)).cloned().collect()
+    }
+
+    // Copies the first n nodes
+    pub fn take(&self, n: usize) -> List<T, P> {
+        self.iter().take(n).cloned().collect()
+    }
+
+    // Indexed updates copy the first i nodes (path copying), the rest is shared
+    //
+    //     self:        1 -> 2 -> 3 -> 4
+    //     set(1, 9):   1' -> 9 ----> 3 -> 4 (same nodes as self)
+    pub fn set(&self, i: usize, val: T) -> List<T, P> {
+        assert!(i < self.len, "Cannot set a nonexistent index");
+        self.copy_prefix(i, self.skip(i + 1).cons(val))
+    }
+
+    pub fn insert(&self, i: usize, val: T) -> List<T, P> {
+        assert!(i <= self.len, "Cannot insert at a nonexistent index");
+        self.copy_prefix(i, self.skip(i).cons(val))
+    }
+
+    pub fn remove(&self, i: usize) -> List<T, P> {
+        assert!(i < self.len, "Cannot remove a nonexistent index");
+        self.copy_prefix(i, self.skip(i + 1))
+    }
+
+    // Clones of the first n values in front of suffix
+    fn copy_prefix(&self, n: usize, suffix: List<T, P>) -> List<T, P> {
+        let prefix: Vec<_> = self.iter().take(n).collect();
+        prefix
+            .into_iter()
+            .rev()
+            .fold(suffix, |list, val| list.cons(val.clone()))
+    }
 }
 
-impl<'a, T> Iterator for Iter<'a, T> {
+impl<T, P: PointerKind> Default for List<T, P> {
+    fn default() -> Self {
+        Self { head: None, len: 0 }
+    }
+}
+
+// Only bumps the head count, whole list is shared
+impl<T, P: PointerKind> Clone for List<T, P> {
+    fn clone(&self) -> Self {
+        Self {
+            head: self.head.clone(),
+            len: self.len,
+        }
+    }
+}
+
+// Derive would want P::Pointer<Node>: Debug, print the values instead
+impl<T: Debug, P: PointerKind> Debug for List<T, P> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
+
+// Same element by element, in order
+impl<T: PartialEq, P: PointerKind> PartialEq for List<T, P> {
+    fn eq(&self, other: &Self) -> bool {
+        self.len == other.len && self.iter().eq(other.iter())
+    }
+}
+
+impl<T: Eq, P: PointerKind> Eq for List<T, P> {}
+
+impl<T: PartialOrd, P: PointerKind> PartialOrd for List<T, P> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        self.iter().partial_cmp(other.iter())
+    }
+}
+
+impl<T: Ord, P: PointerKind> Ord for List<T, P> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.iter().cmp(other.iter())
+    }
+}
+
+// Same as a slice with the same values
+impl<T: Hash, P: PointerKind> Hash for List<T, P> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.len().hash(state);
+        for val in self {
+            val.hash(state);
+        }
+    }
+}
+
+// First value ends up as head, same order as iter()
+impl<T, P: PointerKind> FromIterator<T> for List<T, P> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let vals: Vec<_> = iter.into_iter().collect();
+        vals.into_iter()
+            .rev()
+            .fold(List::default(), |list, val| list.cons(val))
+    }
+}
+
+impl<'a, T, P: PointerKind> IntoIterator for &'a List<T, P> {
+    type Item = &'a T;
+    type IntoIter = Iter<'a, T, P>;
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+pub struct Iter<'a, T, P: PointerKind> {
+    next: Option<&'a Node<T, P>>,
+    len: usize,
+}
+
+impl<'a, T, P: PointerKind> Iterator for Iter<'a, T, P> {
     type Item = &'a T;
     fn next(&mut self) -> Option<Self::Item> {
         self.next.map(|node| {
             self.next = node.next.as_deref();
+            self.len -= 1;
             &node.val
         })
     }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len, Some(self.len))
+    }
 }
 
-impl<T> Drop for List<T> {
+impl<T, P: PointerKind> ExactSizeIterator for Iter<'_, T, P> {}
+
+impl<T, P: PointerKind> Drop for List<T, P> {
     fn drop(&mut self) {
         let mut tail = self.head.take();
         while let Some(node) = tail {
-            // Rc::try_unwrap will take ownership of the node if there is only one strong reference count
-            match Rc::try_unwrap(node) {
-                Ok(mut node) => tail = node.next.take(),
+            // Rc::into_inner (Arc::into_inner) will take ownership of the node if there is only one strong reference count
+            match P::into_inner(node) {
+                Some(mut node) => tail = node.next.take(),
                 _ => break,
             }
 
@@ -78,13 +317,29 @@ impl<T> Drop for List<T> {
     }
 }
 
+// Serialized as a sequence from head to last node, same order as iter()
+#[cfg(feature = "serde")]
+impl<T: serde::Serialize, P: PointerKind> serde::Serialize for List<T, P> {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(self.iter())
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de, T: serde::Deserialize<'de>, P: PointerKind> serde::Deserialize<'de> for List<T, P> {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let vals = <Vec<T> as serde::Deserialize>::deserialize(deserializer)?;
+        Ok(vals.into_iter().collect())
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
     #[test]
     fn test() {
-        let mut list = List::new();
+        let list = List::new();
         let list = list.prepend(1).prepend(2).prepend(3);
         let mut iter = list.iter();
         assert_eq!(iter.next(), Some(&3));
@@ -100,4 +355,265 @@ mod tests {
         let list = list.tail();
         assert_eq!(list.head(), None);
     }
+
+    #[test]
+    fn test_cons() {
+        let empty = List::new();
+        assert!(empty.uncons().is_none());
+        assert_eq!(empty.len(), 0);
+        assert!(empty.tail().is_empty());
+
+        let list = empty.cons(1).cons(2);
+        assert_eq!(list.len(), 2);
+        let (head, tail) = list.uncons().unwrap();
+        assert_eq!(*head, 2);
+        assert_eq!(tail.len(), 1);
+        assert_eq!(tail.head(), Some(&1));
+        // Old versions are left alone
+        assert!(empty.is_empty());
+
+        // Clone shares every node
+        let copy = list.clone();
+        assert!(std::ptr::eq(copy.head().unwrap(), list.head().unwrap()));
+        assert_eq!(copy, list);
+    }
+
+    #[test]
+    fn test_functional() {
+        let list: List<i32> = (1..=5).collect();
+        assert_eq!(list.iter().copied().collect::<Vec<_>>(), &[1, 2, 3, 4, 5]);
+        assert_eq!(list.iter().len(), 5);
+        assert_eq!(list.len(), 5);
+
+        let reversed = list.reverse();
+        assert_eq!(
+            reversed.iter().copied().collect::<Vec<_>>(),
+            &[5, 4, 3, 2, 1]
+        );
+        assert_eq!(reversed.reverse(), list);
+
+        assert_eq!(list.fold(0, |sum, val| sum + val), 15);
+        let strings = list.map(|val| val.to_string());
+        assert_eq!(strings.head().map(String::as_str), Some("1"));
+        assert_eq!(strings.len(), 5);
+        let even = list.filter(|val| val % 2 == 0);
+        assert_eq!(even.iter().copied().collect::<Vec<_>>(), &[2, 4]);
+        assert_eq!(even.len(), 2);
+
+        let front = list.take(2);
+        assert_eq!(front.iter().copied().collect::<Vec<_>>(), &[1, 2]);
+        assert_eq!(list.take(10), list);
+        let back = list.skip(2);
+        assert_eq!(back.iter().copied().collect::<Vec<_>>(), &[3, 4, 5]);
+        assert_eq!(back.len(), 3);
+        assert!(list.skip(10).is_empty());
+        // skip shares the suffix
+        assert!(std::ptr::eq(
+            back.head().unwrap(),
+            list.iter().nth(2).unwrap()
+        ));
+
+        // append copies the left side only
+        let joined = front.append(&back);
+        assert_eq!(joined, list);
+        assert_eq!(joined.len(), 5);
+        assert!(!std::ptr::eq(joined.head().unwrap(), front.head().unwrap()));
+        assert!(std::ptr::eq(
+            joined.skip(2).head().unwrap(),
+            back.head().unwrap()
+        ));
+
+        let mut sum = 0;
+        for val in &list {
+            sum += val;
+        }
+        assert_eq!(sum, 15);
+    }
+
+    #[test]
+    fn test_path_copying() {
+        fn node(list: &List<i32>) -> &Rc<Node<i32, RcK>> {
+            list.head.as_ref().unwrap()
+        }
+
+        let list: List<i32> = (0..5).collect();
+
+        let set = list.set(2, 9);
+        assert_eq!(set.iter().copied().collect::<Vec<_>>(), &[0, 1, 9, 3, 4]);
+        assert_eq!(set.len(), 5);
+        // First nodes are copies, everything after the index is shared
+        assert!(!Rc::ptr_eq(node(&set), node(&list)));
+        assert!(Rc::ptr_eq(node(&set.skip(3)), node(&list.skip(3))));
+
+        let inserted = list.insert(2, 9);
+        assert_eq!(
+            inserted.iter().copied().collect::<Vec<_>>(),
+            &[0, 1, 9, 2, 3, 4]
+        );
+        assert_eq!(inserted.len(), 6);
+        assert!(Rc::ptr_eq(node(&inserted.skip(3)), node(&list.skip(2))));
+
+        let removed = list.remove(2);
+        assert_eq!(removed.iter().copied().collect::<Vec<_>>(), &[0, 1, 3, 4]);
+        assert_eq!(removed.len(), 4);
+        assert!(Rc::ptr_eq(node(&removed.skip(2)), node(&list.skip(3))));
+
+        // Ends of the list
+        assert_eq!(list.insert(0, 9).tail(), list);
+        assert!(Rc::ptr_eq(node(&list.insert(0, 9).tail()), node(&list)));
+        assert_eq!(list.insert(5, 9).skip(5).head(), Some(&9));
+        assert!(Rc::ptr_eq(node(&list.remove(0)), node(&list.tail())));
+        assert_eq!(list.remove(4), list.take(4));
+        assert_eq!(list.set(4, 9).skip(4).head(), Some(&9));
+
+        // Old version never changed
+        assert_eq!(list.iter().copied().collect::<Vec<_>>(), &[0, 1, 2, 3, 4]);
+    }
+
+    #[test]
+    #[should_panic(expected = "Cannot set a nonexistent index")]
+    fn test_set_out_of_range() {
+        let list: List<i32> = (0..5).collect();
+        list.set(5, 9);
+    }
+
+    #[test]
+    #[should_panic(expected = "Cannot insert at a nonexistent index")]
+    fn test_insert_out_of_range() {
+        let list: List<i32> = (0..5).collect();
+        list.insert(6, 9);
+    }
+
+    #[test]
+    #[should_panic(expected = "Cannot remove a nonexistent index")]
+    fn test_remove_out_of_range() {
+        List::<i32>::new().remove(0);
+    }
+
+    #[test]
+    fn test_cmp() {
+        use std::collections::hash_map::DefaultHasher;
+
+        fn hash<T: Hash + ?Sized>(val: &T) -> u64 {
+            let mut hasher = DefaultHasher::new();
+            val.hash(&mut hasher);
+            hasher.finish()
+        }
+
+        let a: List<i32> = vec![1, 2, 3].into_iter().collect();
+        let b = List::new().cons(3).cons(2).cons(1);
+        let c: List<i32> = vec![1, 2].into_iter().collect();
+        let d: List<i32> = vec![1, 3].into_iter().collect();
+
+        assert_eq!(a, b);
+        assert_ne!(a, c);
+        assert_eq!(hash(&a), hash(&b));
+        // Hashes like a slice with the same values
+        assert_eq!(hash(&a), hash(&[1, 2, 3][..]));
+
+        assert!(c < a);
+        assert!(a < d);
+        assert_eq!(a.cmp(&b), std::cmp::Ordering::Equal);
+        assert_eq!(format!("{:?}", a), "[1, 2, 3]");
+    }
+
+    #[test]
+    fn test_threads() {
+        fn assert_send_sync<T: Send + Sync>() {}
+        assert_send_sync::<List<i32, ArcK>>();
+
+        let list = List::new_sync().prepend(1).prepend(2);
+        std::thread::scope(|s| {
+            for t in 0..4 {
+                // Every thread builds its own version on top of the shared one
+                let base = list.tail();
+                s.spawn(move || {
+                    let mine = base.prepend(t * 10).prepend(t * 10 + 1);
+                    assert_eq!(
+                        mine.iter().copied().collect::<Vec<_>>(),
+                        &[t * 10 + 1, t * 10, 1]
+                    );
+                    mine
+                });
+            }
+        });
+
+        // Versions dropped on other threads left the shared part alone
+        assert_eq!(list.iter().copied().collect::<Vec<_>>(), &[2, 1]);
+        let versions: Vec<_> = std::thread::scope(|s| {
+            let handles: Vec<_> = (0..4)
+                .map(|t| {
+                    let base = list.tail();
+                    s.spawn(move || base.prepend(t))
+                })
+                .collect();
+            handles.into_iter().map(|h| h.join().unwrap()).collect()
+        });
+        for (t, version) in versions.iter().enumerate() {
+            assert_eq!(version.head(), Some(&t));
+            assert_eq!(version.tail().head(), Some(&1));
+        }
+    }
+
+    #[test]
+    fn test_long_drop() {
+        // Drop is iterative for both kinds, no stack overflow
+        let mut list = List::new();
+        let mut list_sync = List::new_sync();
+        for i in 0..200_000 {
+            list = list.prepend(i);
+            list_sync = list_sync.prepend(i);
+        }
+        // Shared tail outlives the head
+        let tail = list_sync.tail().tail();
+        drop(list);
+        drop(list_sync);
+        assert_eq!(tail.head(), Some(&199_997));
+    }
+
+    #[test]
+    fn test_long_drop_threads() {
+        // Two threads drop the last two handles to the same chain at once,
+        // one of them must still take it apart node by node
+        // Small stacks, so a recursive drop of the chain would overflow
+        let barrier = std::sync::Barrier::new(2);
+        for _ in 0..20 {
+            let list = (0..100_000).fold(List::new_sync(), |list, i| list.prepend(i));
+            std::thread::scope(|s| {
+                for list in [list.clone(), list] {
+                    let barrier = &barrier;
+                    std::thread::Builder::new()
+                        .stack_size(64 * 1024)
+                        .spawn_scoped(s, move || {
+                            barrier.wait();
+                            drop(list);
+                        })
+                        .unwrap();
+                }
+            });
+        }
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_serde() {
+        use crate::test_serializer::{from_value, to_value, Value};
+
+        let list = List::new();
+        let list = list.prepend(1).prepend(2).prepend(3);
+        // Shared tail serializes like any other list
+        let tail = list.tail();
+        assert_eq!(
+            to_value(&tail).unwrap(),
+            Value::Seq(vec![Value::Int(2), Value::Int(1)])
+        );
+
+        let value = to_value(&list).unwrap();
+        assert_eq!(
+            value,
+            Value::Seq(vec![Value::Int(3), Value::Int(2), Value::Int(1)])
+        );
+        let list: List<i32> = from_value(value).unwrap();
+        assert_eq!(list.iter().copied().collect::<Vec<_>>(), &[3, 2, 1]);
+    }
 }
@@ -0,0 +1,660 @@
+use super::{ArcK, List, PointerKind, RcK};
+use std::cmp::Ordering;
+use std::fmt::{self, Debug};
+use std::hash::{Hash, Hasher};
+
+// Persistent random-access list (Okasaki's skew binary random-access list)
+// Same shape as List, but values live in complete binary trees instead of a chain of nodes,
+// kept in a List from the smallest tree to the largest
+//
+//     values: 0 1 2 3 4 5 6 7 8
+//     trees:  [0] -> [1] -> [2 (3 4 5) (6 7 8)]      sizes 1, 1, 7 (2^k - 1)
+//                           ^ preorder: root, left subtree, right subtree
+//
+// Tree sizes follow skew binary numbers: only the two smallest trees may have the same size,
+// so cons either adds a leaf or joins those two under a new root, never more than that: O(1)
+// There are O(log n) trees, each O(log n) deep, so get and update are O(log n)
+
+enum Tree<T, P: PointerKind> {
+    Leaf(T),
+    Node(T, P::Pointer<Tree<T, P>>, P::Pointer<Tree<T, P>>),
+}
+
+impl<T, P: PointerKind> Tree<T, P> {
+    fn val(&self) -> &T {
+        match self {
+            Tree::Leaf(val) | Tree::Node(val, ..) => val,
+        }
+    }
+}
+
+impl<T: Clone, P: PointerKind> Tree<T, P> {
+    // Copies the path down to i, every subtree next to it is shared
+    fn update(&self, size: usize, i: usize, val: T) -> Self {
+        match self {
+            Tree::Leaf(_) => Tree::Leaf(val),
+            Tree::Node(_, left, right) if i == 0 => Tree::Node(val, left.clone(), right.clone()),
+            Tree::Node(root, left, right) => {
+                let half = size / 2;
+                if i <= half {
+                    let left = P::new(left.update(half, i - 1, val));
+                    Tree::Node(root.clone(), left, right.clone())
+                } else {
+                    let right = P::new(right.update(half, i - 1 - half, val));
+                    Tree::Node(root.clone(), left.clone(), right)
+                }
+            }
+        }
+    }
+}
+
+struct Digit<T, P: PointerKind> {
+    // Number of values in tree
+    size: usize,
+    tree: P::Pointer<Tree<T, P>>,
+}
+
+// Only bumps the tree count, needed to share digits between versions of the List
+impl<T, P: PointerKind> Clone for Digit<T, P> {
+    fn clone(&self) -> Self {
+        Self {
+            size: self.size,
+            tree: self.tree.clone(),
+        }
+    }
+}
+
+pub struct RaList<T, P: PointerKind = RcK> {
+    trees: List<Digit<T, P>, P>,
+    len: usize,
+}
+
+impl<T> RaList<T> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl<T> RaList<T, ArcK> {
+    pub fn new_sync() -> Self {
+        Self::default()
+    }
+}
+
+impl<T, P: PointerKind> RaList<T, P> {
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn cons(&self, val: T) -> Self {
+        let mut trees = self.trees.iter();
+        let trees = match (trees.next(), trees.next()) {
+            // Two smallest trees have the same size, join them under val
+            (Some(left), Some(right)) if left.size == right.size => {
+                self.trees.skip(2).cons(Digit {
+                    size: 1 + left.size + right.size,
+                    tree: P::new(Tree::Node(val, left.tree.clone(), right.tree.clone())),
+                })
+            }
+            _ => self.trees.cons(Digit {
+                size: 1,
+                tree: P::new(Tree::Leaf(val)),
+            }),
+        };
+        Self {
+            trees,
+            len: self.len + 1,
+        }
+    }
+
+    pub fn prepend(&self, val: T) -> Self {
+        self.cons(val)
+    }
+
+    // Split into head and tail, None if empty
+    // Removing the root of the first tree leaves its two subtrees in front
+    pub fn uncons(&self) -> Option<(&T, Self)> {
+        self.trees.uncons().map(|(first, rest)| {
+            let trees = match &*first.tree {
+                Tree::Leaf(_) => rest,
+                Tree::Node(_, left, right) => {
+                    let half = first.size / 2;
+                    rest.cons(Digit {
+                        size: half,
+                        tree: right.clone(),
+                    })
+                    .cons(Digit {
+                        size: half,
+                        tree: left.clone(),
+                    })
+                }
+            };
+            let tail = Self {
+                trees,
+                len: self.len - 1,
+            };
+            (first.tree.val(), tail)
+        })
+    }
+
+    pub fn tail(&self) -> Self {
+        self.uncons().map(|(_, tail)| tail).unwrap_or_default()
+    }
+
+    pub fn head(&self) -> Option<&T> {
+        self.trees.head().map(|first| first.tree.val())
+    }
+
+    pub fn get(&self, mut i: usize) -> Option<&T> {
+        // Skip whole trees first
+        for digit in &self.trees {
+            if i >= digit.size {
+                i -= digit.size;
+                continue;
+            }
+
+            // Then go down the one holding i
+            let mut size = digit.size;
+            let mut tree = &*digit.tree;
+            loop {
+                match tree {
+                    _ if i == 0 => return Some(tree.val()),
+                    Tree::Leaf(_) => unreachable!(),
+                    Tree::Node(_, left, right) => {
+                        size /= 2;
+                        if i <= size {
+                            tree = left;
+                            i -= 1;
+                        } else {
+                            tree = right;
+                            i -= 1 + size;
+                        }
+                    }
+                }
+            }
+        }
+        None
+    }
+
+    pub fn iter(&self) -> Iter<'_, T, P> {
+        Iter {
+            trees: self.trees.iter(),
+            stack: Vec::new(),
+            len: self.len,
+        }
+    }
+
+    pub fn fold<B, F: FnMut(B, &T) -> B>(&self, init: B, f: F) -> B {
+        self.iter().fold(init, f)
+    }
+
+    pub fn map<U, F: FnMut(&T) -> U>(&self, f: F) -> RaList<U, P> {
+        self.iter().map(f).collect()
+    }
+
+    // Drops whole trees first, then splits the one holding n like uncons: O(log n)
+    // Every tree left over is shared
+    pub fn skip(&self, n: usize) -> Self {
+        let mut trees = self.trees.clone();
+        let mut to_skip = n;
+        while to_skip > 0 {
+            let Some((first, rest)) = trees.uncons() else {
+                break;
+            };
+            trees = match &*first.tree {
+                _ if to_skip >= first.size => {
+                    to_skip -= first.size;
+                    rest
+                }
+                Tree::Leaf(_) => unreachable!(),
+                Tree::Node(_, left, right) => {
+                    to_skip -= 1;
+                    let half = first.size / 2;
+                    rest.cons(Digit {
+                        size: half,
+                        tree: right.clone(),
+                    })
+                    .cons(Digit {
+                        size: half,
+                        tree: left.clone(),
+                    })
+                }
+            };
+        }
+        Self {
+            trees,
+            len: self.len.saturating_sub(n),
+        }
+    }
+}
+
+impl<T: Clone, P: PointerKind> RaList<T, P> {
+    // Copies the path to i in its tree, and the trees in front of it in the list
+    pub fn update(&self, mut i: usize, val: T) -> Self {
+        assert!(i < self.len, "Cannot update a nonexistent index");
+        let (k, digit) = self
+            .trees
+            .iter()
+            .enumerate()
+            .find(|(_, digit)| {
+                let found = i < digit.size;
+                if !found {
+                    i -= digit.size;
+                }
+                found
+            })
+            .unwrap();
+
+        let digit = Digit {
+            size: digit.size,
+            tree: P::new(digit.tree.update(digit.size, i, val)),
+        };
+        Self {
+            trees: self.trees.set(k, digit),
+            len: self.len,
+        }
+    }
+
+    pub fn set(&self, i: usize, val: T) -> Self {
+        self.update(i, val)
+    }
+
+    // Rest of List's API, same cost as on List: O(n), cloning the values they rebuild with cons
+    // insert and remove share the trees after i through skip
+    pub fn reverse(&self) -> Self {
+        self.fold(Self::default(), |list, val| list.cons(val.clone()))
+    }
+
+    // Copies the values of self, other is shared
+    pub fn append(&self, other: &Self) -> Self {
+        self.copy_prefix(self.len, other.clone())
+    }
+
+    pub fn filter<F: FnMut(&T) -> bool>(&self, mut f: F) -> Self {
+        self.iter().filter(|val| f(val)).cloned().collect()
+    }
+
+    pub fn take(&self, n: usize) -> Self {
+        self.iter().take(n).cloned().collect()
+    }
+
+    pub fn insert(&self, i: usize, val: T) -> Self {
+        assert!(i <= self.len, "Cannot insert at a nonexistent index");
+        self.copy_prefix(i, self.skip(i).cons(val))
+    }
+
+    pub fn remove(&self, i: usize) -> Self {
+        assert!(i < self.len, "Cannot remove a nonexistent index");
+        self.copy_prefix(i, self.skip(i + 1))
+    }
+
+    // Clones of the first n values in front of suffix
+    fn copy_prefix(&self, n: usize, suffix: Self) -> Self {
+        let prefix: Vec<_> = self.iter().take(n).collect();
+        prefix
+            .into_iter()
+            .rev()
+            .fold(suffix, |list, val| list.cons(val.clone()))
+    }
+}
+
+impl<T, P: PointerKind> Default for RaList<T, P> {
+    fn default() -> Self {
+        Self {
+            trees: List::default(),
+            len: 0,
+        }
+    }
+}
+
+// Only bumps the count of the first tree list node, whole list is shared
+impl<T, P: PointerKind> Clone for RaList<T, P> {
+    fn clone(&self) -> Self {
+        Self {
+            trees: self.trees.clone(),
+            len: self.len,
+        }
+    }
+}
+
+impl<T: Debug, P: PointerKind> Debug for RaList<T, P> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
+
+impl<T: PartialEq, P: PointerKind> PartialEq for RaList<T, P> {
+    fn eq(&self, other: &Self) -> bool {
+        self.len == other.len && self.iter().eq(other.iter())
+    }
+}
+
+impl<T: Eq, P: PointerKind> Eq for RaList<T, P> {}
+
+impl<T: PartialOrd, P: PointerKind> PartialOrd for RaList<T, P> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        self.iter().partial_cmp(other.iter())
+    }
+}
+
+impl<T: Ord, P: PointerKind> Ord for RaList<T, P> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.iter().cmp(other.iter())
+    }
+}
+
+// Same as List (and a slice) with the same values
+impl<T: Hash, P: PointerKind> Hash for RaList<T, P> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.len().hash(state);
+        for val in self {
+            val.hash(state);
+        }
+    }
+}
+
+// First value ends up as head, same order as iter()
+impl<T, P: PointerKind> FromIterator<T> for RaList<T, P> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let vals: Vec<_> = iter.into_iter().collect();
+        vals.into_iter()
+            .rev()
+            .fold(RaList::default(), |list, val| list.cons(val))
+    }
+}
+
+impl<'a, T, P: PointerKind> IntoIterator for &'a RaList<T, P> {
+    type Item = &'a T;
+    type IntoIter = Iter<'a, T, P>;
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+// Preorder walk of every tree, smallest tree first
+pub struct Iter<'a, T, P: PointerKind> {
+    trees: super::Iter<'a, Digit<T, P>, P>,
+    // Subtrees still to visit in the current tree, next one on top
+    stack: Vec<&'a Tree<T, P>>,
+    len: usize,
+}
+
+impl<'a, T, P: PointerKind> Iterator for Iter<'a, T, P> {
+    type Item = &'a T;
+    fn next(&mut self) -> Option<Self::Item> {
+        let tree = match self.stack.pop() {
+            Some(tree) => tree,
+            None => &*self.trees.next()?.tree,
+        };
+        if let Tree::Node(_, left, right) = tree {
+            self.stack.push(right);
+            self.stack.push(left);
+        }
+        self.len -= 1;
+        Some(tree.val())
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len, Some(self.len))
+    }
+}
+
+impl<T, P: PointerKind> ExactSizeIterator for Iter<'_, T, P> {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::rc::Rc;
+
+    // Sizes are 2^k - 1, growing, only the first two may be equal
+    fn check<T, P: PointerKind>(list: &RaList<T, P>) {
+        let sizes: Vec<_> = list.trees.iter().map(|digit| digit.size).collect();
+        assert_eq!(sizes.iter().sum::<usize>(), list.len());
+        for (i, &size) in sizes.iter().enumerate() {
+            assert!((size + 1).is_power_of_two());
+            if let Some(&next) = sizes.get(i + 1) {
+                assert!(size < next || (i == 0 && size == next));
+            }
+        }
+    }
+
+    #[test]
+    fn test() {
+        let list = RaList::new();
+        assert!(list.is_empty());
+        assert_eq!(list.head(), None);
+        assert_eq!(list.get(0), None);
+        assert!(list.uncons().is_none());
+        assert!(list.tail().is_empty());
+
+        let list = list.cons(1).cons(2).cons(3);
+        assert_eq!(list.len(), 3);
+        assert_eq!(list.iter().copied().collect::<Vec<_>>(), &[3, 2, 1]);
+        assert_eq!(format!("{:?}", list), "[3, 2, 1]");
+        assert_eq!(list.head(), Some(&3));
+        let (head, list) = list.uncons().unwrap();
+        assert_eq!(*head, 3);
+        assert_eq!(list.head(), Some(&2));
+        let list = list.tail();
+        assert_eq!(list.head(), Some(&1));
+        let list = list.tail();
+        assert_eq!(list.head(), None);
+
+        // Same trees as the picture at the top
+        let list: RaList<i32> = (0..9).collect();
+        let sizes: Vec<_> = list.trees.iter().map(|digit| digit.size).collect();
+        assert_eq!(sizes, &[1, 1, 7]);
+        assert_eq!(list.get(5), Some(&5));
+    }
+
+    #[test]
+    fn test_get() {
+        let mut list = RaList::new();
+        for n in 0..200 {
+            // Values count down from the head: get(i) == n - 1 - i
+            for i in 0..n {
+                assert_eq!(list.get(i), Some(&(n - 1 - i)));
+            }
+            assert_eq!(list.get(n), None);
+            assert_eq!(list.iter().len(), n);
+            check(&list);
+            list = list.cons(n);
+        }
+
+        // tail takes trees apart again, down to nothing
+        for n in (0..200).rev() {
+            assert_eq!(list.head(), Some(&n));
+            assert_eq!(list.get(n), Some(&0));
+            list = list.tail();
+            check(&list);
+        }
+        assert!(list.is_empty());
+    }
+
+    #[test]
+    fn test_update() {
+        let list: RaList<i32> = (0..20).collect();
+        check(&list);
+        for i in 0..20 {
+            let updated = list.update(i, -1);
+            assert_eq!(updated.len(), 20);
+            assert_eq!(updated.get(i), Some(&-1));
+            let expected: Vec<_> = (0..20)
+                .map(|j| if j == i as i32 { -1 } else { j })
+                .collect();
+            assert_eq!(updated.iter().copied().collect::<Vec<_>>(), expected);
+            assert_eq!(list.set(i, -1), updated);
+        }
+        // Old version never changed
+        assert_eq!(list, (0..20).collect());
+
+        // Only the path to the value is copied, trees after it are shared
+        let updated = list.update(0, -1);
+        let trees: Vec<_> = list.trees.iter().map(|digit| &digit.tree).collect();
+        let new_trees: Vec<_> = updated.trees.iter().map(|digit| &digit.tree).collect();
+        assert!(!Rc::ptr_eq(trees[0], new_trees[0]));
+        for (old, new) in trees.iter().zip(&new_trees).skip(1) {
+            assert!(Rc::ptr_eq(old, new));
+        }
+        // And inside the tree, the subtree off the path
+        let updated = list.update(list.len() - 1, -1);
+        let old_tree = &list.trees.iter().last().unwrap().tree;
+        let new_tree = &updated.trees.iter().last().unwrap().tree;
+        let (Tree::Node(_, old_left, old_right), Tree::Node(_, new_left, new_right)) =
+            (&**old_tree, &**new_tree)
+        else {
+            panic!("Last tree should be a node");
+        };
+        assert!(Rc::ptr_eq(old_left, new_left));
+        assert!(!Rc::ptr_eq(old_right, new_right));
+    }
+
+    #[test]
+    #[should_panic(expected = "Cannot update a nonexistent index")]
+    fn test_update_out_of_range() {
+        let list: RaList<i32> = (0..5).collect();
+        list.update(5, 0);
+    }
+
+    #[test]
+    fn test_skip() {
+        let list: RaList<i32> = (0..50).collect();
+        for n in 0..=51 {
+            let skipped = list.skip(n);
+            check(&skipped);
+            assert_eq!(skipped.len(), 50usize.saturating_sub(n));
+            assert!(skipped.iter().copied().eq(n as i32..50));
+        }
+
+        // Whole trees past the skipped values are shared
+        let skipped = list.skip(2);
+        let old_last = &list.trees.iter().last().unwrap().tree;
+        let new_last = &skipped.trees.iter().last().unwrap().tree;
+        assert!(Rc::ptr_eq(old_last, new_last));
+    }
+
+    #[test]
+    fn test_edits() {
+        let list: RaList<i32> = (0..10).collect();
+        for i in 0..=10 {
+            let inserted = list.insert(i, -1);
+            check(&inserted);
+            let mut expected: Vec<_> = (0..10).collect();
+            expected.insert(i, -1);
+            assert_eq!(inserted.iter().copied().collect::<Vec<_>>(), expected);
+            assert_eq!(inserted.remove(i), list);
+        }
+        for i in 0..10 {
+            let removed = list.remove(i);
+            check(&removed);
+            assert_eq!(removed.len(), 9);
+            assert!(removed
+                .iter()
+                .copied()
+                .eq((0..10).filter(|&j| j != i as i32)));
+        }
+
+        let other: RaList<i32> = (10..15).collect();
+        let appended = list.append(&other);
+        check(&appended);
+        assert!(appended.iter().copied().eq(0..15));
+        assert!(list.append(&RaList::new()).iter().eq(&list));
+        assert!(RaList::new().append(&list).iter().eq(&list));
+
+        assert!(list.reverse().iter().copied().eq((0..10).rev()));
+        assert!(list
+            .filter(|val| val % 2 == 0)
+            .iter()
+            .copied()
+            .eq((0..10).step_by(2)));
+        assert!(list.take(4).iter().copied().eq(0..4));
+        assert!(list.take(20).iter().eq(&list));
+        // Old version never changed
+        assert_eq!(list, (0..10).collect());
+    }
+
+    #[test]
+    #[should_panic(expected = "Cannot insert at a nonexistent index")]
+    fn test_insert_out_of_range() {
+        let list: RaList<i32> = (0..5).collect();
+        list.insert(6, 0);
+    }
+
+    #[test]
+    #[should_panic(expected = "Cannot remove a nonexistent index")]
+    fn test_remove_out_of_range() {
+        let list: RaList<i32> = (0..5).collect();
+        list.remove(5);
+    }
+
+    #[test]
+    fn test_same_as_list() {
+        // Same calls on both, same answers
+        let mut list = List::new();
+        let mut ra_list = RaList::new();
+        for i in 0..50 {
+            list = list.cons(i);
+            ra_list = ra_list.prepend(i);
+        }
+        assert!(list.iter().eq(ra_list.iter()));
+        assert_eq!(
+            list.fold(0, |sum, val| sum + val),
+            ra_list.fold(0, |sum, val| sum + val)
+        );
+        let list = list.tail().set(10, -1);
+        let ra_list = ra_list.tail().set(10, -1);
+        assert!(list.iter().eq(&ra_list));
+        assert_eq!(
+            ra_list
+                .map(|val| val * 2)
+                .iter()
+                .copied()
+                .collect::<Vec<_>>(),
+            list.map(|val| val * 2).iter().copied().collect::<Vec<_>>()
+        );
+        assert!(list.reverse().iter().eq(&ra_list.reverse()));
+        assert!(list.append(&list).iter().eq(&ra_list.append(&ra_list)));
+        assert!(list.skip(7).iter().eq(&ra_list.skip(7)));
+        assert!(list.take(7).iter().eq(&ra_list.take(7)));
+        assert!(list
+            .insert(3, 100)
+            .remove(20)
+            .iter()
+            .eq(&ra_list.insert(3, 100).remove(20)));
+        assert!(list
+            .filter(|val| val % 3 == 0)
+            .iter()
+            .eq(&ra_list.filter(|val| val % 3 == 0)));
+
+        // Hashes the same as List, so either can key the same map
+        use std::collections::hash_map::DefaultHasher;
+        let hash = |val: &dyn Fn(&mut DefaultHasher)| {
+            let mut hasher = DefaultHasher::new();
+            val(&mut hasher);
+            hasher.finish()
+        };
+        assert_eq!(hash(&|h| list.hash(h)), hash(&|h| ra_list.hash(h)));
+    }
+
+    #[test]
+    fn test_threads() {
+        fn assert_send_sync<T: Send + Sync>() {}
+        assert_send_sync::<RaList<i32, ArcK>>();
+
+        let list = (0..100).fold(RaList::new_sync(), |list, i| list.cons(i));
+        std::thread::scope(|s| {
+            for t in 0..4 {
+                let list = &list;
+                s.spawn(move || {
+                    let mine = list.update(t * 10, -1);
+                    assert_eq!(mine.get(t * 10), Some(&-1));
+                    assert_eq!(list.get(t * 10), Some(&(99 - t as i32 * 10)));
+                });
+            }
+        });
+    }
+}
@@ -0,0 +1,392 @@
+use std::cell::{Cell, OnceCell};
+use std::fmt::{self, Debug};
+use std::rc::Rc;
+
+// Lazy persistent list: like List, but every node is only built when someone looks at it,
+// and then remembered, so later readers (and other versions sharing it) get the same node
+//
+//     stream -> [1 | next] -> [2 | next] -> (thunk, not forced yet)
+//
+// Unforced parts are a closure producing the next node, so a stream can be infinite
+// Streams are Rc based, OnceCell can't be shared between threads
+
+type Thunk<T> = Box<dyn FnOnce() -> Option<Node<T>>>;
+
+struct Node<T> {
+    val: T,
+    next: Stream<T>,
+}
+
+struct Lazy<T> {
+    // None inside: stream is empty
+    node: OnceCell<Option<Node<T>>>,
+    // Taken by the first force
+    thunk: Cell<Option<Thunk<T>>>,
+}
+
+pub struct Stream<T> {
+    lazy: Rc<Lazy<T>>,
+}
+
+impl<T> Stream<T> {
+    pub fn new() -> Self {
+        Self::forced(None)
+    }
+
+    // New stream with val in front of next, next is shared and stays as lazy as it is
+    pub fn cons(val: T, next: Stream<T>) -> Self {
+        Self::forced(Some(Node { val, next }))
+    }
+
+    fn forced(node: Option<Node<T>>) -> Self {
+        Self {
+            lazy: Rc::new(Lazy {
+                node: OnceCell::from(node),
+                thunk: Cell::new(None),
+            }),
+        }
+    }
+
+    // Build the node on first access only, f returns None for an empty stream
+    pub fn lazy<F: FnOnce() -> Option<(T, Stream<T>)> + 'static>(f: F) -> Self {
+        let thunk: Thunk<T> = Box::new(|| f().map(|(val, next)| Node { val, next }));
+        Self {
+            lazy: Rc::new(Lazy {
+                node: OnceCell::new(),
+                thunk: Cell::new(Some(thunk)),
+            }),
+        }
+    }
+
+    fn force(&self) -> Option<&Node<T>> {
+        self.lazy
+            .node
+            .get_or_init(|| {
+                // Thunk is gone but node was never set: it is running right now (we are inside it),
+                // or it panicked last time and took the node with it
+                let thunk = self.lazy.thunk.take();
+                thunk.expect("Stream poisoned by a panicking thunk or forced re-entrantly")()
+            })
+            .as_ref()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.force().is_none()
+    }
+
+    pub fn head(&self) -> Option<&T> {
+        self.force().map(|node| &node.val)
+    }
+
+    pub fn tail(&self) -> Stream<T> {
+        self.force()
+            .map(|node| node.next.clone())
+            .unwrap_or_default()
+    }
+
+    // Split into head and tail, None if empty
+    pub fn uncons(&self) -> Option<(&T, Stream<T>)> {
+        self.force().map(|node| (&node.val, node.next.clone()))
+    }
+
+    // Forces the stream as it goes, never ends on an infinite stream
+    pub fn iter(&self) -> Iter<'_, T> {
+        Iter { next: Some(self) }
+    }
+}
+
+// Constructors and adapters hand closures to later forces, so they have to own everything
+impl<T: 'static> Stream<T> {
+    // seed, f(seed), f(f(seed)), ...
+    pub fn iterate<F: Fn(&T) -> T + 'static>(seed: T, f: F) -> Self {
+        Self::iterate_rc(seed, Rc::new(f))
+    }
+
+    fn iterate_rc<F: Fn(&T) -> T + 'static>(seed: T, f: Rc<F>) -> Self {
+        Self::lazy(move || {
+            let next = f(&seed);
+            Some((seed, Self::iterate_rc(next, f)))
+        })
+    }
+
+    // Values produced by f from state, until it returns None
+    pub fn unfold<S: 'static, F: Fn(S) -> Option<(T, S)> + 'static>(state: S, f: F) -> Self {
+        Self::unfold_rc(state, Rc::new(f))
+    }
+
+    fn unfold_rc<S: 'static, F: Fn(S) -> Option<(T, S)> + 'static>(state: S, f: Rc<F>) -> Self {
+        Self::lazy(move || f(state).map(|(val, state)| (val, Self::unfold_rc(state, f))))
+    }
+
+    pub fn map<U: 'static, F: Fn(&T) -> U + 'static>(&self, f: F) -> Stream<U> {
+        self.map_rc(Rc::new(f))
+    }
+
+    fn map_rc<U: 'static, F: Fn(&T) -> U + 'static>(&self, f: Rc<F>) -> Stream<U> {
+        let stream = self.clone();
+        Stream::lazy(move || {
+            stream
+                .force()
+                .map(|node| (f(&node.val), node.next.map_rc(f.clone())))
+        })
+    }
+}
+
+// Adapters copy values out of the nodes, the source stream keeps its own
+impl<T: Clone + 'static> Stream<T> {
+    pub fn repeat(val: T) -> Self {
+        Self::lazy(move || Some((val.clone(), Self::repeat(val))))
+    }
+
+    pub fn filter<F: Fn(&T) -> bool + 'static>(&self, f: F) -> Self {
+        self.filter_rc(Rc::new(f))
+    }
+
+    fn filter_rc<F: Fn(&T) -> bool + 'static>(&self, f: Rc<F>) -> Self {
+        let stream = self.clone();
+        Self::lazy(move || {
+            // Skip with a loop, a long run of rejected values must not recurse
+            let mut cur = stream;
+            loop {
+                let next = match cur.force() {
+                    None => return None,
+                    Some(node) if f(&node.val) => {
+                        return Some((node.val.clone(), node.next.filter_rc(f)))
+                    }
+                    Some(node) => node.next.clone(),
+                };
+                cur = next;
+            }
+        })
+    }
+
+    // Pairs until either stream ends
+    pub fn zip<U: Clone + 'static>(&self, other: &Stream<U>) -> Stream<(T, U)> {
+        let (stream, other) = (self.clone(), other.clone());
+        Stream::lazy(move || {
+            let (a, b) = (stream.force()?, other.force()?);
+            Some(((a.val.clone(), b.val.clone()), a.next.zip(&b.next)))
+        })
+    }
+
+    pub fn take(&self, n: usize) -> Self {
+        if n == 0 {
+            return Self::new();
+        }
+        let stream = self.clone();
+        Self::lazy(move || {
+            stream
+                .force()
+                .map(|node| (node.val.clone(), node.next.take(n - 1)))
+        })
+    }
+}
+
+impl<T> Default for Stream<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// Shares every node, forced or not
+impl<T> Clone for Stream<T> {
+    fn clone(&self) -> Self {
+        Self {
+            lazy: self.lazy.clone(),
+        }
+    }
+}
+
+// Only shows what is forced already, printing must not force an infinite stream
+impl<T: Debug> Debug for Stream<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut list = f.debug_list();
+        let mut cur = self;
+        while let Some(forced) = cur.lazy.node.get() {
+            match forced {
+                Some(node) => {
+                    list.entry(&node.val);
+                    cur = &node.next;
+                }
+                None => return list.finish(),
+            }
+        }
+        list.finish_non_exhaustive()
+    }
+}
+
+impl<T> Drop for Stream<T> {
+    fn drop(&mut self) {
+        // Like List, unlink forced nodes one by one while we are the only owner,
+        // dropping them recursively would overflow the stack on a long forced stream
+        let mut next = Rc::get_mut(&mut self.lazy).and_then(|lazy| lazy.node.take().flatten());
+        while let Some(mut node) = next {
+            next = Rc::get_mut(&mut node.next.lazy).and_then(|lazy| lazy.node.take().flatten());
+        }
+    }
+}
+
+impl<'a, T> IntoIterator for &'a Stream<T> {
+    type Item = &'a T;
+    type IntoIter = Iter<'a, T>;
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+pub struct Iter<'a, T> {
+    next: Option<&'a Stream<T>>,
+}
+
+impl<'a, T> Iterator for Iter<'a, T> {
+    type Item = &'a T;
+    fn next(&mut self) -> Option<Self::Item> {
+        let node = self.next?.force();
+        self.next = node.map(|node| &node.next);
+        node.map(|node| &node.val)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn collect<T: Clone>(stream: &Stream<T>) -> Vec<T> {
+        stream.iter().cloned().collect()
+    }
+
+    #[test]
+    fn test() {
+        let stream: Stream<i32> = Stream::new();
+        assert!(stream.is_empty());
+        assert_eq!(stream.head(), None);
+        assert!(stream.uncons().is_none());
+
+        let stream = Stream::cons(1, Stream::cons(2, Stream::new()));
+        assert_eq!(stream.head(), Some(&1));
+        let (head, tail) = stream.uncons().unwrap();
+        assert_eq!(*head, 1);
+        assert_eq!(tail.head(), Some(&2));
+        assert!(tail.tail().is_empty());
+        assert_eq!(collect(&stream), &[1, 2]);
+
+        let mut sum = 0;
+        for val in &stream {
+            sum += val;
+        }
+        assert_eq!(sum, 3);
+    }
+
+    #[test]
+    fn test_memoized() {
+        let calls = Rc::new(Cell::new(0));
+        let counter = calls.clone();
+        let stream = Stream::iterate(0, move |val| {
+            counter.set(counter.get() + 1);
+            val + 1
+        });
+        // Nothing runs before it's needed
+        assert_eq!(calls.get(), 0);
+
+        assert_eq!(
+            stream.iter().take(5).copied().collect::<Vec<_>>(),
+            &[0, 1, 2, 3, 4]
+        );
+        assert_eq!(calls.get(), 5);
+        // Forced nodes are remembered, by this stream and by every shared tail
+        let tail = stream.tail().tail();
+        assert_eq!(tail.iter().take(3).copied().collect::<Vec<_>>(), &[2, 3, 4]);
+        assert_eq!(stream.iter().take(5).count(), 5);
+        assert_eq!(calls.get(), 5);
+        assert!(Rc::ptr_eq(&stream.tail().lazy, &stream.tail().lazy));
+
+        assert_eq!(format!("{:?}", stream.take(3)), "[..]");
+        assert_eq!(format!("{:?}", tail), "[2, 3, 4, ..]");
+        // Built with cons, so nothing left to force
+        let finite = Stream::cons(1, Stream::new());
+        assert_eq!(format!("{:?}", finite), "[1]");
+    }
+
+    #[test]
+    fn test_infinite() {
+        assert_eq!(collect(&Stream::repeat('a').take(3)), &['a', 'a', 'a']);
+        let powers = Stream::iterate(1u64, |val| val * 2);
+        assert_eq!(collect(&powers.take(5)), &[1, 2, 4, 8, 16]);
+        assert!(Stream::repeat(1).take(0).is_empty());
+
+        let fib = Stream::unfold((0u64, 1u64), |(a, b)| Some((a, (b, a + b))));
+        assert_eq!(collect(&fib.take(10)), &[0, 1, 1, 2, 3, 5, 8, 13, 21, 34]);
+
+        let countdown = Stream::unfold(3, |n| (n > 0).then_some((n, n - 1)));
+        assert_eq!(collect(&countdown), &[3, 2, 1]);
+    }
+
+    #[test]
+    fn test_adapters() {
+        let forced = Rc::new(Cell::new(0));
+        let counter = forced.clone();
+        let naturals = Stream::unfold(0, move |n| {
+            counter.set(counter.get() + 1);
+            Some((n, n + 1))
+        });
+
+        let squares = naturals.map(|n| n * n);
+        let even = squares.filter(|n| n % 2 == 0);
+        let pairs = naturals.zip(&even).take(4);
+        // Building adapters forces nothing
+        assert_eq!(forced.get(), 0);
+
+        assert_eq!(collect(&pairs), &[(0, 0), (1, 4), (2, 16), (3, 36)]);
+        // Only as far as the 4th even square (6 * 6)
+        assert_eq!(forced.get(), 7);
+
+        // Source stream is left alone
+        assert_eq!(naturals.head(), Some(&0));
+        assert_eq!(collect(&naturals.take(3)), &[0, 1, 2]);
+
+        // Ends as soon as either side does
+        let short = Stream::cons('a', Stream::cons('b', Stream::new()));
+        assert_eq!(collect(&naturals.zip(&short)), &[(0, 'a'), (1, 'b')]);
+        assert!(short.filter(|_| false).is_empty());
+    }
+
+    #[test]
+    fn test_poisoned() {
+        let stream: Stream<i32> = Stream::lazy(|| panic!("thunk exploded"));
+        let first = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| stream.head()));
+        assert!(first.is_err());
+
+        // Nothing to run anymore, every later force reports it
+        let again = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| stream.is_empty()));
+        let msg = *again.unwrap_err().downcast::<String>().unwrap();
+        assert_eq!(
+            msg,
+            "Stream poisoned by a panicking thunk or forced re-entrantly"
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "Stream poisoned by a panicking thunk or forced re-entrantly")]
+    fn test_reentrant() {
+        let cell: Rc<OnceCell<Stream<i32>>> = Rc::new(OnceCell::new());
+        let inner = cell.clone();
+        let stream = Stream::lazy(move || inner.get().unwrap().uncons().map(|(v, s)| (*v, s)));
+        cell.set(stream.clone()).ok().unwrap();
+        stream.head();
+    }
+
+    #[test]
+    fn test_long() {
+        // Long runs skipped by filter don't recurse
+        let sparse = Stream::iterate(0u32, |n| n + 1).filter(|n| n % 1_000_000 == 999_999);
+        assert_eq!(sparse.head(), Some(&999_999));
+
+        // Drop of a long forced chain doesn't overflow the stack
+        let stream = Stream::iterate(0u32, |n| n + 1);
+        assert_eq!(stream.iter().nth(1_000_000), Some(&1_000_000));
+        let mapped = stream.map(|n| n / 2);
+        assert_eq!(mapped.iter().nth(1_000_000), Some(&500_000));
+        drop(stream);
+        drop(mapped);
+    }
+}
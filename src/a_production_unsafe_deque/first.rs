@@ -1,31 +1,303 @@
-use std::fmt::Debug;
+use super::raw::{self, Ends, RawList};
+use std::alloc::{alloc, dealloc, handle_alloc_error, Layout};
+use std::cmp::Ordering;
+use std::error::Error;
+use std::fmt::{Debug, Display, Formatter};
 use std::hash::{Hash, Hasher};
 use std::marker::PhantomData;
-use std::ptr::NonNull;
+use std::mem;
+use std::ops::{Bound, Index, IndexMut, RangeBounds};
+use std::ptr::{self, NonNull};
+use std::sync::atomic::{self, AtomicUsize};
 
-struct Node<T> {
+pub(super) struct Node<T> {
     val: T,
     next: Option<NonNull<Node<T>>>,
     prev: Option<NonNull<Node<T>>>,
+    // Block of pool slots this node lives in, None if it was allocated on its own
+    // Set once when memory is allocated, stays with the node whichever list it moves to
+    chunk: Option<NonNull<Chunk>>,
 }
 
-struct LinkedList<T> {
+pub struct LinkedList<T, A: NodeAllocator = Global> {
     head: Option<NonNull<Node<T>>>,
     tail: Option<NonNull<Node<T>>>,
     len: usize,
+    // Freed nodes are kept here for reuse instead of going back to the allocator
+    pool: Option<NodePool<T>>,
+    // Where every node of this list comes from and goes back to
+    alloc: A,
     _phantom: PhantomData<T>,
 }
 
+/// Source of node memory for LinkedList
+///
+/// # Safety
+///
+/// Memory returned by `allocate` must be valid for the requested layout until it is deallocated.
+/// Memory allocated by one allocator must be deallocatable by any other one it is `same_as`,
+/// because nodes move between lists (append, split_off, splice, ...).
+/// A clone must be `same_as` the allocator it was cloned from
+pub unsafe trait NodeAllocator: Clone {
+    // None means out of memory, list will report it through handle_alloc_error
+    fn allocate(&self, layout: Layout) -> Option<NonNull<u8>>;
+
+    /// # Safety
+    ///
+    /// `ptr` must come from `allocate` of this allocator (or one `same_as` it) with the same layout
+    unsafe fn deallocate(&self, ptr: NonNull<u8>, layout: Layout);
+
+    // Whether other can free memory allocated by self, lists only exchange nodes if it can
+    // e.g. two arenas are never the same, even if they are of the same type
+    fn same_as(&self, other: &Self) -> bool;
+}
+
+// Default allocator, forwards to the global allocator like Box does
+#[derive(Debug, Default, Clone, Copy)]
+pub struct Global;
+
+unsafe impl NodeAllocator for Global {
+    fn allocate(&self, layout: Layout) -> Option<NonNull<u8>> {
+        // Node always has next and prev, so layout is never zero sized
+        NonNull::new(unsafe { alloc(layout) })
+    }
+
+    unsafe fn deallocate(&self, ptr: NonNull<u8>, layout: Layout) {
+        dealloc(ptr.as_ptr(), layout)
+    }
+
+    // There's only one global allocator
+    fn same_as(&self, _other: &Self) -> bool {
+        true
+    }
+}
+
+// Lets several lists borrow one allocator, e.g. an arena living on the stack
+// Borrows of two different arenas are told apart by the arenas themselves
+unsafe impl<A: NodeAllocator> NodeAllocator for &A {
+    fn allocate(&self, layout: Layout) -> Option<NonNull<u8>> {
+        (**self).allocate(layout)
+    }
+
+    unsafe fn deallocate(&self, ptr: NonNull<u8>, layout: Layout) {
+        (**self).deallocate(ptr, layout)
+    }
+
+    fn same_as(&self, other: &Self) -> bool {
+        (**self).same_as(*other)
+    }
+}
+
+// Free list of node slots, linked through next pointer, val of a pooled slot is uninitialized
+// reserve allocates all missing slots at once, as one chunk:
+//
+//     [Chunk | slot | slot | slot | ...]
+//
+// Nodes can still move between lists (append, split_off, ...) and be freed by any of them,
+// so a chunk isn't owned by a pool. It counts its slots instead, last one released frees it
+// Pool doesn't own an allocator, list passes its own one in and frees the pool on drop
+struct NodePool<T> {
+    free: Option<NonNull<Node<T>>>,
+    len: usize,
+}
+
+impl<T> NodePool<T> {
+    fn new() -> Self {
+        Self { free: None, len: 0 }
+    }
+
+    fn push(&mut self, slot: NonNull<Node<T>>) {
+        unsafe { ptr::addr_of_mut!((*slot.as_ptr()).next).write(self.free) };
+        self.free = Some(slot);
+        self.len += 1;
+    }
+
+    fn pop(&mut self) -> Option<NonNull<Node<T>>> {
+        let slot = self.free?;
+        self.free = unsafe { ptr::addr_of!((*slot.as_ptr()).next).read() };
+        self.len -= 1;
+        Some(slot)
+    }
+
+    fn reserve<A: NodeAllocator>(&mut self, additional: usize, alloc: &A) {
+        let missing = additional.saturating_sub(self.len);
+        if missing == 0 {
+            return;
+        }
+
+        let (layout, offset) = Layout::array::<Node<T>>(missing)
+            .and_then(|slots| Layout::new::<Chunk>().extend(slots))
+            .expect("Too many nodes to reserve");
+        let chunk: NonNull<Chunk> = match alloc.allocate(layout) {
+            Some(chunk) => chunk.cast(),
+            None => handle_alloc_error(layout),
+        };
+
+        unsafe {
+            chunk.as_ptr().write(Chunk {
+                live: AtomicUsize::new(missing),
+                layout,
+            });
+            let first = chunk.as_ptr().cast::<u8>().add(offset).cast::<Node<T>>();
+            for i in 0..missing {
+                let slot = NonNull::new_unchecked(first.add(i));
+                ptr::addr_of_mut!((*slot.as_ptr()).chunk).write(Some(chunk));
+                self.push(slot);
+            }
+        }
+    }
+
+    fn clear<A: NodeAllocator>(&mut self, alloc: &A) {
+        // Slots hold no value, so only free memory, never drop them as Node<T>
+        while let Some(slot) = self.pop() {
+            unsafe { release_slot(slot, alloc) };
+        }
+    }
+}
+
+// Header of a block of pool slots
+struct Chunk {
+    // Slots not released yet, they may sit in lists on other threads
+    live: AtomicUsize,
+    // Whole block, header included
+    layout: Layout,
+}
+
+// Uninitialized memory for one node, not part of any chunk
+fn allocate_node<T, A: NodeAllocator>(alloc: &A) -> NonNull<Node<T>> {
+    let layout = Layout::new::<Node<T>>();
+    match alloc.allocate(layout) {
+        Some(slot) => {
+            let slot = slot.cast::<Node<T>>();
+            unsafe { ptr::addr_of_mut!((*slot.as_ptr()).chunk).write(None) };
+            slot
+        }
+        None => handle_alloc_error(layout),
+    }
+}
+
+// Give memory of a node back, a chunk goes back with its last slot
+// SAFETY: slot must come from allocate_node or reserve of an allocator same_as alloc,
+// its val must be moved out already, and it must not be used after this call
+unsafe fn release_slot<T, A: NodeAllocator>(slot: NonNull<Node<T>>, alloc: &A) {
+    match ptr::addr_of!((*slot.as_ptr()).chunk).read() {
+        None => alloc.deallocate(slot.cast(), Layout::new::<Node<T>>()),
+        Some(chunk) => {
+            // Same as dropping the last Arc: Release here and Acquire in whoever frees the chunk,
+            // so every other slot is done with before the memory goes away
+            let live = &(*chunk.as_ptr()).live;
+            if live.fetch_sub(1, atomic::Ordering::Release) == 1 {
+                atomic::fence(atomic::Ordering::Acquire);
+                let layout = (*chunk.as_ptr()).layout;
+                alloc.deallocate(chunk.cast(), layout);
+            }
+        }
+    }
+}
+
 impl<T> LinkedList<T> {
     pub fn new() -> Self {
+        Self::new_in(Global)
+    }
+
+    // List which recycles freed nodes, so push after pop doesn't allocate
+    pub fn with_pool() -> Self {
+        Self::with_pool_in(Global)
+    }
+}
+
+impl<T, A: NodeAllocator> LinkedList<T, A> {
+    pub fn new_in(alloc: A) -> Self {
+        Self {
+            head: None,
+            tail: None,
+            len: 0,
+            pool: None,
+            alloc,
+            _phantom: PhantomData,
+        }
+    }
+
+    pub fn with_pool_in(alloc: A) -> Self {
         Self {
             head: None,
             tail: None,
             len: 0,
+            pool: Some(NodePool::new()),
+            alloc,
             _phantom: PhantomData,
         }
     }
 
+    pub fn allocator(&self) -> &A {
+        &self.alloc
+    }
+
+    // Make sure at least additional nodes can be pushed without allocation, enables pool if needed
+    pub fn reserve_nodes(&mut self, additional: usize) {
+        self.pool
+            .get_or_insert_with(NodePool::new)
+            .reserve(additional, &self.alloc);
+    }
+
+    // Give every pooled node back to the allocator, pool stays enabled
+    pub fn shrink_to_fit(&mut self) {
+        if let Some(pool) = self.pool.as_mut() {
+            pool.clear(&self.alloc);
+        }
+    }
+
+    pub fn pooled_nodes(&self) -> usize {
+        self.pool.as_ref().map_or(0, |pool| pool.len)
+    }
+
+    fn alloc_node(&mut self, val: T) -> NonNull<Node<T>> {
+        let slot = match self.pool.as_mut().and_then(NodePool::pop) {
+            Some(slot) => slot,
+            None => allocate_node::<T, A>(&self.alloc),
+        };
+        // chunk is already set, it never changes
+        unsafe {
+            let slot = slot.as_ptr();
+            ptr::addr_of_mut!((*slot).val).write(val);
+            ptr::addr_of_mut!((*slot).next).write(None);
+            ptr::addr_of_mut!((*slot).prev).write(None);
+        }
+        slot
+    }
+
+    // Take value out of node, then recycle or free the node
+    // SAFETY: node must be unlinked, and not used after this call
+    unsafe fn free_node(&mut self, node: NonNull<Node<T>>) -> T {
+        match self.pool.as_mut() {
+            Some(pool) => {
+                let val = ptr::addr_of!((*node.as_ptr()).val).read();
+                pool.push(node);
+                val
+            }
+            None => {
+                let val = ptr::addr_of!((*node.as_ptr()).val).read();
+                release_slot(node, &self.alloc);
+                val
+            }
+        }
+    }
+
+    // Nodes of other will be freed by self, or the other way around
+    fn assert_same_allocator(&self, other: &Self) {
+        assert!(
+            self.alloc.same_as(&other.alloc),
+            "Lists use different allocators"
+        );
+    }
+
+    // Exchange nodes with other list, pools stay where they are
+    fn swap_nodes(&mut self, other: &mut Self) {
+        mem::swap(&mut self.head, &mut other.head);
+        mem::swap(&mut self.tail, &mut other.tail);
+        mem::swap(&mut self.len, &mut other.len);
+    }
+
     pub fn is_empty(&self) -> bool {
         self.len == 0
     }
@@ -36,11 +308,7 @@ impl<T> LinkedList<T> {
 
     pub fn push_front(&mut self, val: T) {
         unsafe {
-            let new_head = NonNull::new_unchecked(Box::into_raw(Box::new(Node {
-                val,
-                next: None,
-                prev: None,
-            })));
+            let new_head = self.alloc_node(val);
 
             if let Some(old_head) = self.head {
                 (*old_head.as_ptr()).prev = Some(new_head);
@@ -56,11 +324,7 @@ impl<T> LinkedList<T> {
 
     pub fn push_back(&mut self, val: T) {
         unsafe {
-            let new_tail = NonNull::new_unchecked(Box::into_raw(Box::new(Node {
-                val,
-                next: None,
-                prev: None,
-            })));
+            let new_tail = self.alloc_node(val);
 
             if let Some(old_tail) = self.tail {
                 (*old_tail.as_ptr()).next = Some(new_tail);
@@ -77,9 +341,7 @@ impl<T> LinkedList<T> {
     pub fn pop_front(&mut self) -> Option<T> {
         unsafe {
             self.head.map(|old_head| {
-                let old_head = Box::from_raw(old_head.as_ptr());
-
-                self.head = match old_head.next {
+                self.head = match (*old_head.as_ptr()).next {
                     Some(new_head) => {
                         (*new_head.as_ptr()).prev = None;
                         Some(new_head)
@@ -91,7 +353,7 @@ impl<T> LinkedList<T> {
                 };
 
                 self.len -= 1;
-                old_head.val
+                self.free_node(old_head)
             })
         }
     }
@@ -99,9 +361,7 @@ impl<T> LinkedList<T> {
     pub fn pop_back(&mut self) -> Option<T> {
         unsafe {
             self.tail.map(|old_tail| {
-                let old_tail = Box::from_raw(old_tail.as_ptr());
-
-                self.tail = match old_tail.prev {
+                self.tail = match (*old_tail.as_ptr()).prev {
                     Some(new_tail) => {
                         (*new_tail.as_ptr()).next = None;
                         Some(new_tail)
@@ -113,7 +373,7 @@ impl<T> LinkedList<T> {
                 };
 
                 self.len -= 1;
-                old_tail.val
+                self.free_node(old_tail)
             })
         }
     }
@@ -137,17 +397,291 @@ impl<T> LinkedList<T> {
     pub fn len(&self) -> usize {
         self.len
     }
+
+    pub fn append(&mut self, other: &mut Self) {
+        // We have this:
+        //
+        //  self.front -> A <-> B <- self.back
+        //
+        // other.front -> 1 <-> 2 <- other.back
+        //
+        //
+        // Becoming this:
+        //
+        //  self.front -> A <-> B <-> 1 <-> 2 <- self.back
+        //
+        // other is empty
+        //
+        self.assert_same_allocator(other);
+        match self.tail {
+            Some(old_tail) => {
+                if let Some(other_head) = other.head.take() {
+                    unsafe {
+                        (*old_tail.as_ptr()).next = Some(other_head);
+                        (*other_head.as_ptr()).prev = Some(old_tail);
+                    }
+                    self.tail = other.tail.take();
+                    self.len += mem::replace(&mut other.len, 0);
+                }
+            }
+            // Nothing to link with, just take all nodes of other
+            None => self.swap_nodes(other),
+        }
+    }
+
+    pub fn prepend(&mut self, other: &mut Self) {
+        // Append self to the back of other, then take all nodes back
+        // (append checks the allocators)
+        other.append(self);
+        self.swap_nodes(other);
+    }
+
+    pub fn split_off(&mut self, at: usize) -> Self {
+        let len = self.len;
+        assert!(at <= len, "Cannot split off at a nonexistent index");
+
+        if at == 0 {
+            let mut ret = Self::new_in(self.alloc.clone());
+            ret.swap_nodes(self);
+            return ret;
+        } else if at == len {
+            return Self::new_in(self.alloc.clone());
+        }
+
+        // Last node staying in self
+        let split_node = self.node_at(at - 1);
+
+        // We have this:
+        //
+        //     list.front -> A <-> B <-> C <-> D <- list.back
+        //                         ^
+        //                     split_node
+        //
+        //
+        // And we want to produce this:
+        //
+        //     list.front -> A <-> B <- list.back
+        //
+        //   return.front -> C <-> D <- return.back
+        //
+        unsafe {
+            let split_node = split_node.unwrap();
+            let ret_head = (*split_node.as_ptr()).next.take();
+            if let Some(ret_head) = ret_head {
+                (*ret_head.as_ptr()).prev = None;
+            }
+
+            let ret_tail = self.tail.replace(split_node);
+            self.len = at;
+
+            LinkedList {
+                head: ret_head,
+                tail: ret_tail,
+                len: len - at,
+                pool: None,
+                alloc: self.alloc.clone(),
+                _phantom: PhantomData,
+            }
+        }
+    }
+
+    pub fn get(&self, index: usize) -> Option<&T> {
+        self.node_at(index)
+            .map(|node| unsafe { &(*node.as_ptr()).val })
+    }
+
+    pub fn get_mut(&mut self, index: usize) -> Option<&mut T> {
+        self.node_at(index)
+            .map(|node| unsafe { &mut (*node.as_ptr()).val })
+    }
+
+    pub fn insert(&mut self, index: usize, val: T) {
+        if let Err(err) = self.try_insert(index, val) {
+            panic!("{}", err.error());
+        }
+    }
+
+    pub fn try_insert(&mut self, index: usize, val: T) -> Result<(), InsertError<T>> {
+        if index > self.len {
+            return Err(InsertError {
+                error: IndexError::new(index, self.len),
+                val,
+            });
+        }
+
+        // Insert before node at index, or before ghost node to push back
+        let cur = self.node_at(index);
+        CursoMut {
+            cur,
+            index: cur.map(|_| index),
+            list: self,
+        }
+        .insert_before(val);
+        Ok(())
+    }
+
+    pub fn remove(&mut self, index: usize) -> Option<T> {
+        self.try_remove(index).ok()
+    }
+
+    pub fn try_remove(&mut self, index: usize) -> Result<T, IndexError> {
+        let len = self.len;
+        let cur = self
+            .node_at(index)
+            .ok_or_else(|| IndexError::new(index, len))?;
+        let mut cursor = CursoMut {
+            cur: Some(cur),
+            index: Some(index),
+            list: self,
+        };
+        Ok(cursor.remove_current().unwrap())
+    }
+
+    pub fn swap(&mut self, i: usize, j: usize) {
+        let len = self.len;
+        let a = self
+            .node_at(i)
+            .unwrap_or_else(|| panic!("{}", IndexError::new(i, len)));
+        let b = self
+            .node_at(j)
+            .unwrap_or_else(|| panic!("{}", IndexError::new(j, len)));
+
+        // Swap values rather than relinking nodes, two distinct nodes never alias
+        if a != b {
+            unsafe { mem::swap(&mut (*a.as_ptr()).val, &mut (*b.as_ptr()).val) };
+        }
+    }
+
+    // Flip every link in place, no node is allocated or moved
+    pub fn reverse(&mut self) {
+        let mut cur = self.head;
+        while let Some(node) = cur {
+            unsafe {
+                let node = &mut *node.as_ptr();
+                mem::swap(&mut node.next, &mut node.prev);
+                // Old next is now prev
+                cur = node.prev;
+            }
+        }
+        mem::swap(&mut self.head, &mut self.tail);
+    }
+
+    // Move first n elements to the back
+    pub fn rotate_left(&mut self, n: usize) {
+        assert!(n <= self.len, "Cannot rotate by more than the length");
+        if n == 0 || n == self.len {
+            return;
+        }
+
+        // Last node to move to the back, found from whichever end is closer
+        let split_node = self.node_at(n - 1);
+
+        // We have this:
+        //
+        //     list.front -> A <-> B <-> C <-> D <- list.back
+        //                         ^
+        //                     split_node
+        //
+        //
+        // And we want to produce this:
+        //
+        //     list.front -> C <-> D <-> A <-> B <- list.back
+        //
+        unsafe {
+            let split_node = split_node.unwrap();
+            let old_head = self.head.unwrap();
+            let old_tail = self.tail.unwrap();
+            let new_head = (*split_node.as_ptr()).next.take().unwrap();
+
+            (*new_head.as_ptr()).prev = None;
+            (*old_tail.as_ptr()).next = Some(old_head);
+            (*old_head.as_ptr()).prev = Some(old_tail);
+
+            self.head = Some(new_head);
+            self.tail = Some(split_node);
+        }
+    }
+
+    // Move last n elements to the front
+    pub fn rotate_right(&mut self, n: usize) {
+        assert!(n <= self.len, "Cannot rotate by more than the length");
+        self.rotate_left(self.len - n);
+    }
+
+    fn node_at(&self, index: usize) -> Option<NonNull<Node<T>>> {
+        if index >= self.len {
+            return None;
+        }
+
+        // Walk from whichever end is closer to index
+        unsafe {
+            if index <= self.len / 2 {
+                let mut node = self.head;
+                for _ in 0..index {
+                    node = node.and_then(|node| (*node.as_ptr()).next);
+                }
+                node
+            } else {
+                let mut node = self.tail;
+                for _ in index + 1..self.len {
+                    node = node.and_then(|node| (*node.as_ptr()).prev);
+                }
+                node
+            }
+        }
+    }
+}
+
+// Links live in Node, right next to the value
+impl<T, A: NodeAllocator> RawList for LinkedList<T, A> {
+    type Node = Node<T>;
+
+    fn ends(&mut self) -> Ends<'_, Node<T>> {
+        (&mut self.head, &mut self.tail, &mut self.len)
+    }
+
+    unsafe fn next(node: NonNull<Node<T>>) -> Option<NonNull<Node<T>>> {
+        (*node.as_ptr()).next
+    }
+
+    unsafe fn prev(node: NonNull<Node<T>>) -> Option<NonNull<Node<T>>> {
+        (*node.as_ptr()).prev
+    }
+
+    unsafe fn set_next(node: NonNull<Node<T>>, next: Option<NonNull<Node<T>>>) {
+        (*node.as_ptr()).next = next;
+    }
+
+    unsafe fn set_prev(node: NonNull<Node<T>>, prev: Option<NonNull<Node<T>>>) {
+        (*node.as_ptr()).prev = prev;
+    }
 }
 
-impl<T> Drop for LinkedList<T> {
+impl<T, A: NodeAllocator> Drop for LinkedList<T, A> {
     fn drop(&mut self) {
-        while self.pop_front().is_some() {}
+        // If a T::drop panics, we are unwinding out of the loop below with nodes still linked
+        // Guard keeps popping the rest while unwinding, then gives pooled nodes back
+        // (a second panic inside guard aborts, same as std)
+        struct DropGuard<'a, T, A: NodeAllocator>(&'a mut LinkedList<T, A>);
+
+        impl<'a, T, A: NodeAllocator> Drop for DropGuard<'a, T, A> {
+            fn drop(&mut self) {
+                while self.0.pop_front().is_some() {}
+                if let Some(pool) = self.0.pool.as_mut() {
+                    pool.clear(&self.0.alloc);
+                }
+            }
+        }
+
+        // Node is already freed when pop_front returns, so a panicking value never leaks its node
+        let guard = DropGuard(self);
+        while guard.0.pop_front().is_some() {}
     }
 }
 
-struct IntoIter<T>(LinkedList<T>);
+pub struct IntoIter<T, A: NodeAllocator = Global>(LinkedList<T, A>);
 
-impl<T> Iterator for IntoIter<T> {
+impl<T, A: NodeAllocator> Iterator for IntoIter<T, A> {
     type Item = T;
     fn next(&mut self) -> Option<Self::Item> {
         self.0.pop_front()
@@ -157,19 +691,19 @@ impl<T> Iterator for IntoIter<T> {
     }
 }
 
-impl<T> DoubleEndedIterator for IntoIter<T> {
+impl<T, A: NodeAllocator> DoubleEndedIterator for IntoIter<T, A> {
     fn next_back(&mut self) -> Option<Self::Item> {
         self.0.pop_back()
     }
 }
 
-impl<T> ExactSizeIterator for IntoIter<T> {
+impl<T, A: NodeAllocator> ExactSizeIterator for IntoIter<T, A> {
     fn len(&self) -> usize {
         self.0.len
     }
 }
 
-struct Iter<'a, T> {
+pub struct Iter<'a, T> {
     head: Option<NonNull<Node<T>>>,
     tail: Option<NonNull<Node<T>>>,
     len: usize,
@@ -220,7 +754,7 @@ impl<'a, T> ExactSizeIterator for Iter<'a, T> {
     }
 }
 
-struct IterMut<'a, T> {
+pub struct IterMut<'a, T> {
     head: Option<NonNull<Node<T>>>,
     tail: Option<NonNull<Node<T>>>,
     len: usize,
@@ -270,11 +804,77 @@ impl<'a, T> ExactSizeIterator for IterMut<'a, T> {
     }
 }
 
-impl<T> LinkedList<T> {
-    pub fn into_iter(self) -> IntoIter<T> {
-        IntoIter(self)
+pub struct ExtractIf<'a, T, F, A: NodeAllocator = Global>
+where
+    F: FnMut(&mut T) -> bool,
+{
+    list: &'a mut LinkedList<T, A>,
+    // Next node to visit, nodes before it are already checked
+    next: Option<NonNull<Node<T>>>,
+    // Number of nodes not visited yet
+    remaining: usize,
+    pred: F,
+}
+
+impl<'a, T, F, A: NodeAllocator> Iterator for ExtractIf<'a, T, F, A>
+where
+    F: FnMut(&mut T) -> bool,
+{
+    type Item = T;
+    fn next(&mut self) -> Option<Self::Item> {
+        while let Some(node) = self.next {
+            unsafe {
+                self.next = (*node.as_ptr()).next;
+                self.remaining -= 1;
+
+                // Node is only unlinked after predicate returns
+                // -> if predicate panics, list is still untouched at this node
+                if (self.pred)(&mut (*node.as_ptr()).val) {
+                    self.list.unlink_node(node);
+                    return Some(self.list.free_node(node));
+                }
+            }
+        }
+
+        None
     }
 
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (0, Some(self.remaining))
+    }
+}
+
+pub struct Drain<'a, T, A: NodeAllocator = Global> {
+    // Range is detached from the list as soon as Drain is created
+    // -> list is already relinked even if Drain is leaked or dropped partially
+    range: LinkedList<T, A>,
+    _phantom: PhantomData<&'a mut LinkedList<T, A>>,
+}
+
+impl<'a, T, A: NodeAllocator> Iterator for Drain<'a, T, A> {
+    type Item = T;
+    fn next(&mut self) -> Option<Self::Item> {
+        self.range.pop_front()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.range.len, Some(self.range.len))
+    }
+}
+
+impl<'a, T, A: NodeAllocator> DoubleEndedIterator for Drain<'a, T, A> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.range.pop_back()
+    }
+}
+
+impl<'a, T, A: NodeAllocator> ExactSizeIterator for Drain<'a, T, A> {
+    fn len(&self) -> usize {
+        self.range.len
+    }
+}
+
+impl<T, A: NodeAllocator> LinkedList<T, A> {
     pub fn iter(&self) -> Iter<T> {
         Iter {
             head: self.head,
@@ -292,21 +892,169 @@ impl<T> LinkedList<T> {
             _phantom: PhantomData,
         }
     }
-}
-
-// IntoIterator auto deduced a List to a iterator
-// for _ in list <=> for _ in list.into_iter()
-impl<T> IntoIterator for LinkedList<T> {
-    type Item = T;
-    type IntoIter = IntoIter<T>;
 
-    fn into_iter(self) -> Self::IntoIter {
-        self.into_iter()
+    // Remove and yield elements matching predicate, survivors are never moved or reallocated
+    // Dropping iterator early keeps unvisited elements in the list
+    pub fn extract_if<F>(&mut self, pred: F) -> ExtractIf<'_, T, F, A>
+    where
+        F: FnMut(&mut T) -> bool,
+    {
+        ExtractIf {
+            next: self.head,
+            remaining: self.len,
+            list: self,
+            pred,
+        }
     }
-}
 
-// for _ in &list <=> for _ in list.iter()
-impl<'a, T> IntoIterator for &'a LinkedList<T> {
+    pub fn drain<R>(&mut self, range: R) -> Drain<'_, T, A>
+    where
+        R: RangeBounds<usize>,
+    {
+        let len = self.len;
+        let start = match range.start_bound() {
+            Bound::Included(&start) => start,
+            Bound::Excluded(&start) => start
+                .checked_add(1)
+                .expect("attempted to drain from after maximum usize"),
+            Bound::Unbounded => 0,
+        };
+        let end = match range.end_bound() {
+            Bound::Included(&end) => end
+                .checked_add(1)
+                .expect("attempted to drain up to maximum usize"),
+            Bound::Excluded(&end) => end,
+            Bound::Unbounded => len,
+        };
+        assert!(
+            start <= end,
+            "drain start {start} is greater than end {end}"
+        );
+        assert!(
+            end <= len,
+            "drain end {end} is out of range for list of length {len}"
+        );
+
+        let mut range = LinkedList::new_in(self.alloc.clone());
+        if start == end {
+            return Drain {
+                range,
+                _phantom: PhantomData,
+            };
+        }
+
+        // We have this:
+        //
+        //     list.front -> A <-> B <-> C <-> D <- list.back
+        //                         ^     ^
+        //                       first  last
+        //
+        //
+        // And we want to produce this:
+        //
+        //     list.front -> A <-> D <- list.back
+        //
+        //    range.front -> B <-> C <- range.back
+        //
+        unsafe {
+            let first = self.node_at(start).unwrap();
+            let last = self.node_at(end - 1).unwrap();
+
+            let before = (*first.as_ptr()).prev.take();
+            let after = (*last.as_ptr()).next.take();
+
+            match before {
+                Some(before) => (*before.as_ptr()).next = after,
+                None => self.head = after,
+            }
+            match after {
+                Some(after) => (*after.as_ptr()).prev = before,
+                None => self.tail = before,
+            }
+
+            self.len -= end - start;
+            range.head = Some(first);
+            range.tail = Some(last);
+            range.len = end - start;
+        }
+
+        Drain {
+            range,
+            _phantom: PhantomData,
+        }
+    }
+
+    pub fn retain<F>(&mut self, mut f: F)
+    where
+        F: FnMut(&T) -> bool,
+    {
+        self.retain_mut(|val| f(val));
+    }
+
+    pub fn retain_mut<F>(&mut self, mut f: F)
+    where
+        F: FnMut(&mut T) -> bool,
+    {
+        self.extract_if(|val| !f(val)).for_each(drop);
+    }
+
+    // Collapse runs of equal consecutive elements into their first one, returns how many were removed
+    pub fn dedup(&mut self) -> usize
+    where
+        T: PartialEq,
+    {
+        self.dedup_by(|a, b| a == b)
+    }
+
+    pub fn dedup_by_key<K, F>(&mut self, mut key: F) -> usize
+    where
+        K: PartialEq,
+        F: FnMut(&mut T) -> K,
+    {
+        self.dedup_by(|a, b| key(a) == key(b))
+    }
+
+    // same_bucket gets (candidate, last kept element) like Vec::dedup_by, candidate is removed on true
+    // Duplicates are unlinked as we go, survivors never move
+    pub fn dedup_by<F>(&mut self, mut same_bucket: F) -> usize
+    where
+        F: FnMut(&mut T, &mut T) -> bool,
+    {
+        let mut kept = match self.head {
+            Some(node) => node,
+            None => return 0,
+        };
+
+        let mut removed = 0;
+        unsafe {
+            while let Some(node) = (*kept.as_ptr()).next {
+                if same_bucket(&mut (*node.as_ptr()).val, &mut (*kept.as_ptr()).val) {
+                    // List is consistent before the value is dropped, a panicking drop can't break it
+                    self.unlink_node(node);
+                    removed += 1;
+                    drop(self.free_node(node));
+                } else {
+                    kept = node;
+                }
+            }
+        }
+        removed
+    }
+}
+
+// IntoIterator auto deduced a List to a iterator
+// for _ in list <=> for _ in list.into_iter()
+impl<T, A: NodeAllocator> IntoIterator for LinkedList<T, A> {
+    type Item = T;
+    type IntoIter = IntoIter<T, A>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        IntoIter(self)
+    }
+}
+
+// for _ in &list <=> for _ in list.iter()
+impl<'a, T, A: NodeAllocator> IntoIterator for &'a LinkedList<T, A> {
     type Item = &'a T;
     type IntoIter = Iter<'a, T>;
 
@@ -316,7 +1064,7 @@ impl<'a, T> IntoIterator for &'a LinkedList<T> {
 }
 
 // for _ in &mut list <=> for _ in list.iter_mut()
-impl<'a, T> IntoIterator for &'a mut LinkedList<T> {
+impl<'a, T, A: NodeAllocator> IntoIterator for &'a mut LinkedList<T, A> {
     type Item = &'a mut T;
     type IntoIter = IterMut<'a, T>;
 
@@ -325,17 +1073,236 @@ impl<'a, T> IntoIterator for &'a mut LinkedList<T> {
     }
 }
 
+impl<T, A: NodeAllocator> LinkedList<T, A> {
+    pub fn is_sorted(&self) -> bool
+    where
+        T: PartialOrd,
+    {
+        self.iter().is_sorted()
+    }
+
+    pub fn sort(&mut self)
+    where
+        T: Ord,
+    {
+        self.sort_by(T::cmp);
+    }
+
+    pub fn sort_by_key<K, F>(&mut self, mut f: F)
+    where
+        K: Ord,
+        F: FnMut(&T) -> K,
+    {
+        self.sort_by(|a, b| f(a).cmp(&f(b)));
+    }
+
+    // Bottom-up merge sort, merge runs of 1, 2, 4, ... nodes until only one run is left
+    // Nodes are relinked in place, so no allocation and O(1) extra space
+    pub fn sort_by<F>(&mut self, compare: F)
+    where
+        F: FnMut(&T, &T) -> Ordering,
+    {
+        if self.len < 2 {
+            return;
+        }
+
+        let mut sorter = MergeSorter::new(self, compare);
+        let mut width = 1;
+        loop {
+            let mut merges = 0;
+
+            while sorter.q.is_some() {
+                merges += 1;
+
+                // Step width nodes along q chain to cut p run
+                sorter.p = sorter.q;
+                while sorter.psize < width {
+                    match sorter.q {
+                        Some(node) => unsafe {
+                            sorter.q = (*node.as_ptr()).next;
+                            sorter.psize += 1;
+                        },
+                        None => break,
+                    }
+                }
+                sorter.qsize = width;
+
+                sorter.merge_runs();
+            }
+
+            // Terminate merged chain, so next pass can walk it
+            if let Some(out_tail) = sorter.out_tail {
+                unsafe { (*out_tail.as_ptr()).next = None };
+            }
+
+            if merges <= 1 {
+                break;
+            }
+
+            // Start next pass with every node back in q chain
+            sorter.q = sorter.out_head.take();
+            sorter.out_tail = None;
+            width *= 2;
+        }
+    }
+
+    // Merge other into self, both lists must be sorted
+    // Equal elements of self stay before those of other
+    pub fn merge(&mut self, other: &mut Self)
+    where
+        T: Ord,
+    {
+        self.merge_by(other, T::cmp);
+    }
+
+    pub fn merge_by<F>(&mut self, other: &mut Self, compare: F)
+    where
+        F: FnMut(&T, &T) -> Ordering,
+    {
+        self.assert_same_allocator(other);
+        let psize = self.len;
+        let qsize = other.len;
+        let other_head = other.head;
+        self.append(other);
+
+        // Self and other are two adjacent runs, merge them once
+        let mut sorter = MergeSorter::new(self, compare);
+        sorter.p = sorter.q.take();
+        sorter.psize = psize;
+        sorter.q = other_head;
+        sorter.qsize = qsize;
+        sorter.merge_runs();
+    }
+}
+
+// Sorting only follows next pointers, so at any point nodes of the list are:
+//
+//     out_head -> ... -> out_tail    merged output of current pass
+//     p -> ...                       psize nodes of left run
+//     q -> ... -> None               right run (at most qsize nodes), then unvisited nodes
+//
+// Dropping the sorter joins them back, then fixes prev and tail pointers
+// -> list stays valid even if compare panics
+struct MergeSorter<'a, T, F, A: NodeAllocator> {
+    list: &'a mut LinkedList<T, A>,
+    compare: F,
+    out_head: Option<NonNull<Node<T>>>,
+    out_tail: Option<NonNull<Node<T>>>,
+    p: Option<NonNull<Node<T>>>,
+    psize: usize,
+    q: Option<NonNull<Node<T>>>,
+    qsize: usize,
+}
+
+impl<'a, T, F, A: NodeAllocator> MergeSorter<'a, T, F, A>
+where
+    F: FnMut(&T, &T) -> Ordering,
+{
+    fn new(list: &'a mut LinkedList<T, A>, compare: F) -> Self {
+        let head = list.head.take();
+        list.tail = None;
+        Self {
+            list,
+            compare,
+            out_head: None,
+            out_tail: None,
+            p: None,
+            psize: 0,
+            q: head,
+            qsize: 0,
+        }
+    }
+
+    fn merge_runs(&mut self) {
+        loop {
+            let node = unsafe {
+                match (self.p, self.q) {
+                    // Take from p on equal to keep sort stable
+                    (Some(p), Some(q)) if self.psize > 0 && self.qsize > 0 => {
+                        if (self.compare)(&(*p.as_ptr()).val, &(*q.as_ptr()).val)
+                            == Ordering::Greater
+                        {
+                            self.take_q(q)
+                        } else {
+                            self.take_p(p)
+                        }
+                    }
+                    (Some(p), _) if self.psize > 0 => self.take_p(p),
+                    (_, Some(q)) if self.qsize > 0 => self.take_q(q),
+                    _ => break,
+                }
+            };
+
+            match self.out_tail {
+                Some(out_tail) => unsafe { (*out_tail.as_ptr()).next = Some(node) },
+                None => self.out_head = Some(node),
+            }
+            self.out_tail = Some(node);
+        }
+
+        // Left run is used up, unvisited nodes start at q
+        self.p = None;
+        self.psize = 0;
+    }
+
+    unsafe fn take_p(&mut self, p: NonNull<Node<T>>) -> NonNull<Node<T>> {
+        self.p = (*p.as_ptr()).next;
+        self.psize -= 1;
+        p
+    }
+
+    unsafe fn take_q(&mut self, q: NonNull<Node<T>>) -> NonNull<Node<T>> {
+        self.q = (*q.as_ptr()).next;
+        self.qsize -= 1;
+        q
+    }
+}
+
+impl<'a, T, F, A: NodeAllocator> Drop for MergeSorter<'a, T, F, A> {
+    fn drop(&mut self) {
+        unsafe {
+            // Join out -> p run -> q chain
+            let mut head = self.q;
+            if let (Some(p), true) = (self.p, self.psize > 0) {
+                let mut p_last = p;
+                for _ in 1..self.psize {
+                    p_last = (*p_last.as_ptr()).next.unwrap();
+                }
+                (*p_last.as_ptr()).next = head;
+                head = Some(p);
+            }
+            if let Some(out_tail) = self.out_tail {
+                (*out_tail.as_ptr()).next = head;
+                head = self.out_head;
+            }
+
+            // Fix prev pointers that sorting ignored
+            let mut prev = None;
+            let mut cur = head;
+            while let Some(node) = cur {
+                (*node.as_ptr()).prev = prev;
+                prev = Some(node);
+                cur = (*node.as_ptr()).next;
+            }
+
+            self.list.head = head;
+            self.list.tail = prev;
+        }
+    }
+}
+
 // To put our collection to production, we need to implement below traits
 // [Default, Clone, Extend, FromIterator, Debug, PartialEq, Eq, PartialOrd, Ord, Hash]
-impl<T> Default for LinkedList<T> {
+impl<T, A: NodeAllocator + Default> Default for LinkedList<T, A> {
     fn default() -> Self {
-        Self::new()
+        Self::new_in(A::default())
     }
 }
 
-impl<T: Clone> Clone for LinkedList<T> {
+impl<T: Clone, A: NodeAllocator> Clone for LinkedList<T, A> {
     fn clone(&self) -> Self {
-        let mut new_list = LinkedList::new();
+        // If T::clone panics, new_list is dropped while unwinding and frees what was cloned so far
+        let mut new_list = LinkedList::new_in(self.alloc.clone());
         for item in self {
             new_list.push_back(item.clone());
         }
@@ -343,49 +1310,51 @@ impl<T: Clone> Clone for LinkedList<T> {
     }
 }
 
-impl<T> Extend<T> for LinkedList<T> {
+impl<T, A: NodeAllocator> Extend<T> for LinkedList<T, A> {
     fn extend<IntoIter: IntoIterator<Item = T>>(&mut self, iter: IntoIter) {
+        // Each item is linked before the next one is pulled,
+        // so a panicking iterator leaves a valid list holding everything pushed so far
         for item in iter {
             self.push_back(item);
         }
     }
 }
 
-impl<T> FromIterator<T> for LinkedList<T> {
+impl<T, A: NodeAllocator + Default> FromIterator<T> for LinkedList<T, A> {
     fn from_iter<IntoIter: IntoIterator<Item = T>>(iter: IntoIter) -> Self {
-        let mut new_list = LinkedList::new();
+        let mut new_list = LinkedList::default();
         new_list.extend(iter);
         new_list
     }
 }
 
-impl<T: Debug> Debug for LinkedList<T> {
+impl<T: Debug, A: NodeAllocator> Debug for LinkedList<T, A> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         f.debug_list().entries(self).finish()
     }
 }
 
-impl<T: PartialEq> PartialEq for LinkedList<T> {
+impl<T: PartialEq, A: NodeAllocator> PartialEq for LinkedList<T, A> {
     fn eq(&self, other: &Self) -> bool {
         self.len() == other.len() && self.iter().zip(other.iter()).all(|(a, b)| a == b)
     }
 }
 
-impl<T: Eq> Eq for LinkedList<T> {}
+impl<T: Eq, A: NodeAllocator> Eq for LinkedList<T, A> {}
 
-impl<T: PartialOrd> PartialOrd for LinkedList<T> {
+impl<T: PartialOrd, A: NodeAllocator> PartialOrd for LinkedList<T, A> {
     fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
         self.iter().partial_cmp(other.iter())
     }
 }
 
-impl<T: Ord> Ord for LinkedList<T> {
+impl<T: Ord, A: NodeAllocator> Ord for LinkedList<T, A> {
     fn cmp(&self, other: &Self) -> std::cmp::Ordering {
         self.iter().cmp(other.iter())
     }
 }
 
-impl<T: Hash> Hash for LinkedList<T> {
+impl<T: Hash, A: NodeAllocator> Hash for LinkedList<T, A> {
     fn hash<H: Hasher>(&self, state: &mut H) {
         self.len().hash(state);
         for item in self {
@@ -393,85 +1362,296 @@ impl<T: Hash> Hash for LinkedList<T> {
         }
     }
 }
+
+// Serialized as a sequence from front to back
+#[cfg(feature = "serde")]
+impl<T: serde::Serialize, A: NodeAllocator> serde::Serialize for LinkedList<T, A> {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(self)
+    }
+}
+
+// Elements are pushed back as they come, without collecting them first
+#[cfg(feature = "serde")]
+impl<'de, T: serde::Deserialize<'de>, A: NodeAllocator + Default> serde::Deserialize<'de>
+    for LinkedList<T, A>
+{
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        use serde::de::{SeqAccess, Visitor};
+
+        struct SeqVisitor<T, A>(PhantomData<(T, A)>);
+
+        impl<'de, T: serde::Deserialize<'de>, A: NodeAllocator + Default> Visitor<'de>
+            for SeqVisitor<T, A>
+        {
+            type Value = LinkedList<T, A>;
+
+            fn expecting(&self, f: &mut Formatter) -> std::fmt::Result {
+                f.write_str("a sequence")
+            }
+
+            fn visit_seq<S: SeqAccess<'de>>(self, mut seq: S) -> Result<Self::Value, S::Error> {
+                let mut list = LinkedList::default();
+                while let Some(val) = seq.next_element()? {
+                    list.push_back(val);
+                }
+                Ok(list)
+            }
+        }
+
+        deserializer.deserialize_seq(SeqVisitor(PhantomData))
+    }
+}
+
+impl<T, A: NodeAllocator> Index<usize> for LinkedList<T, A> {
+    type Output = T;
+
+    fn index(&self, index: usize) -> &Self::Output {
+        match self.get(index) {
+            Some(val) => val,
+            None => panic!("{}", IndexError::new(index, self.len)),
+        }
+    }
+}
+
+impl<T, A: NodeAllocator> IndexMut<usize> for LinkedList<T, A> {
+    fn index_mut(&mut self, index: usize) -> &mut Self::Output {
+        let len = self.len;
+        match self.get_mut(index) {
+            Some(val) => val,
+            None => panic!("{}", IndexError::new(index, len)),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct IndexError {
+    pub index: usize,
+    pub len: usize,
+}
+
+impl IndexError {
+    fn new(index: usize, len: usize) -> Self {
+        Self { index, len }
+    }
+}
+
+impl Display for IndexError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "index out of bounds: the len is {} but the index is {}",
+            self.len, self.index
+        )
+    }
+}
+
+impl Error for IndexError {}
+
+// Failed insert gives the value back, so caller doesn't lose it
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InsertError<T> {
+    error: IndexError,
+    val: T,
+}
+
+impl<T> InsertError<T> {
+    pub fn error(&self) -> IndexError {
+        self.error
+    }
+
+    pub fn into_inner(self) -> T {
+        self.val
+    }
+}
+
+impl<T> Display for InsertError<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "failed to insert, {}", self.error)
+    }
+}
+
+impl<T: Debug> Error for InsertError<T> {}
+
+// NonNull is !Send and !Sync, so compiler won't derive these for us
+// But we uniquely own our nodes, so we have same thread safety as Box<T>
+unsafe impl<T: Send, A: NodeAllocator + Send> Send for LinkedList<T, A> {}
+unsafe impl<T: Sync, A: NodeAllocator + Sync> Sync for LinkedList<T, A> {}
+
+// Iter behaves like &T, so it can be sent to another thread if &T can be (T: Sync)
+unsafe impl<'a, T: Sync> Send for Iter<'a, T> {}
+unsafe impl<'a, T: Sync> Sync for Iter<'a, T> {}
+
+// IterMut behaves like &mut T
+unsafe impl<'a, T: Send> Send for IterMut<'a, T> {}
+unsafe impl<'a, T: Sync> Sync for IterMut<'a, T> {}
+
+// ExtractIf behaves like &mut LinkedList<T> plus its predicate
+unsafe impl<'a, T: Send, F: Send + FnMut(&mut T) -> bool, A: NodeAllocator + Send> Send
+    for ExtractIf<'a, T, F, A>
+{
+}
+unsafe impl<'a, T: Sync, F: Sync + FnMut(&mut T) -> bool, A: NodeAllocator + Sync> Sync
+    for ExtractIf<'a, T, F, A>
+{
+}
+
+// Cursor behaves like &LinkedList<T>
+unsafe impl<'a, T: Sync, A: NodeAllocator + Sync> Send for Cursor<'a, T, A> {}
+unsafe impl<'a, T: Sync, A: NodeAllocator + Sync> Sync for Cursor<'a, T, A> {}
+
+// CursoMut behaves like &mut LinkedList<T>
+unsafe impl<'a, T: Send, A: NodeAllocator + Send> Send for CursoMut<'a, T, A> {}
+unsafe impl<'a, T: Sync, A: NodeAllocator + Sync> Sync for CursoMut<'a, T, A> {}
 //
 
-struct CursoMut<'a, T> {
+pub struct Cursor<'a, T, A: NodeAllocator = Global> {
     cur: Option<NonNull<Node<T>>>,
-    list: &'a mut LinkedList<T>,
+    list: &'a LinkedList<T, A>,
     index: Option<usize>,
 }
 
-impl<'a, T> CursoMut<'a, T> {
+// Cursor only reads the list, so it is safe to copy it around like a shared reference
+impl<'a, T, A: NodeAllocator> Clone for Cursor<'a, T, A> {
+    fn clone(&self) -> Self {
+        Self {
+            cur: self.cur,
+            list: self.list,
+            index: self.index,
+        }
+    }
+}
+
+impl<'a, T, A: NodeAllocator> Cursor<'a, T, A> {
     pub fn index(&self) -> Option<usize> {
         self.index
     }
 
-    pub fn current(&self) -> Option<&mut T> {
-        self.cur.map(|node| unsafe { &mut (*node.as_ptr()).val })
+    // Returned references live as long as the list, not the cursor
+    // Because the list can't be modified while a shared cursor borrows it
+    pub fn current(&self) -> Option<&'a T> {
+        self.cur.map(|node| unsafe { &(*node.as_ptr()).val })
     }
 
-    pub fn next(&self) -> Option<&mut T> {
-        match self.cur {
-            Some(node) => unsafe { (*node.as_ptr()).next.map(|node| &mut (*node.as_ptr()).val) },
-            _ => self
-                .list
-                .head
-                .map(|node| unsafe { &mut (*node.as_ptr()).val }),
-        }
+    pub fn peek_next(&self) -> Option<&'a T> {
+        let next = unsafe { raw::next_of::<LinkedList<T, A>>(self.cur, self.list.head) };
+        next.map(|node| unsafe { &(*node.as_ptr()).val })
     }
 
-    pub fn previous(&self) -> Option<&mut T> {
-        match self.cur {
-            Some(node) => unsafe { (*node.as_ptr()).prev.map(|node| &mut (*node.as_ptr()).val) },
-            _ => self
-                .list
-                .tail
-                .map(|node| unsafe { &mut (*node.as_ptr()).val }),
-        }
+    pub fn peek_prev(&self) -> Option<&'a T> {
+        let prev = unsafe { raw::prev_of::<LinkedList<T, A>>(self.cur, self.list.tail) };
+        prev.map(|node| unsafe { &(*node.as_ptr()).val })
     }
 
     pub fn move_next(&mut self) {
-        match self.cur {
-            Some(node) => {
-                unsafe {
-                    self.cur = (*node.as_ptr()).next;
-                    // Need to check next node is a ghost node
-                    match (self.cur.is_some(), self.index.as_mut()) {
-                        (true, Some(index)) => *index += 1,
-                        _ => self.index = None,
-                    }
-                }
-            }
-            _ if !self.list.is_empty() => {
-                // We are at pre-head (or ghost) node, like -1 index
-                self.index = Some(0);
-                self.cur = self.list.head;
-            }
-            _ => {}
-        }
+        unsafe {
+            raw::move_next::<LinkedList<T, A>>(&mut self.cur, &mut self.index, self.list.head)
+        };
     }
 
     pub fn move_prev(&mut self) {
-        match self.cur {
-            Some(node) => {
-                unsafe {
-                    self.cur = (*node.as_ptr()).prev;
-                }
-                match (self.cur.is_some(), self.index.as_mut()) {
-                    (true, Some(index)) => *index -= 1,
-                    _ => self.index = None,
-                }
-            }
-            _ if !self.list.is_empty() => {
-                // We are at post-tail (or ghost) node
-                self.cur = self.list.tail;
-                self.index = Some(self.list.len() - 1);
-            }
-            _ => {}
+        let (tail, len) = (self.list.tail, self.list.len);
+        unsafe { raw::move_prev::<LinkedList<T, A>>(&mut self.cur, &mut self.index, tail, len) };
+    }
+}
+
+/// Mutable cursor over a [`LinkedList`].
+///
+/// Mutable references handed out by the cursor borrow the cursor itself,
+/// so two of them can never alias:
+///
+/// ```compile_fail
+/// use rust_too_many_linked_lists::a_production_unsafe_deque::first::LinkedList;
+///
+/// let mut list: LinkedList<i32> = (0..3).collect();
+/// let mut cursor = list.cursor_front_mut();
+/// let a = cursor.current().unwrap();
+/// let b = cursor.current().unwrap();
+/// *a += *b;
+/// ```
+///
+/// ```compile_fail
+/// use rust_too_many_linked_lists::a_production_unsafe_deque::first::LinkedList;
+///
+/// let mut list: LinkedList<i32> = (0..3).collect();
+/// let mut cursor = list.cursor_front_mut();
+/// let cur = cursor.current().unwrap();
+/// let next = cursor.peek_next().unwrap();
+/// std::mem::swap(cur, next);
+/// ```
+///
+/// The cursor can't be moved or edited while a reference is alive:
+///
+/// ```compile_fail
+/// use rust_too_many_linked_lists::a_production_unsafe_deque::first::LinkedList;
+///
+/// let mut list: LinkedList<i32> = (0..3).collect();
+/// let mut cursor = list.cursor_front_mut();
+/// let cur = cursor.current().unwrap();
+/// cursor.remove_current();
+/// *cur += 1;
+/// ```
+///
+/// Shared access goes through a read-only [`Cursor`] view:
+///
+/// ```compile_fail
+/// use rust_too_many_linked_lists::a_production_unsafe_deque::first::LinkedList;
+///
+/// let mut list: LinkedList<i32> = (0..3).collect();
+/// let mut cursor = list.cursor_front_mut();
+/// let view = cursor.as_cursor();
+/// let cur = cursor.current().unwrap();
+/// assert_eq!(view.current(), Some(&*cur));
+/// ```
+pub struct CursoMut<'a, T, A: NodeAllocator = Global> {
+    cur: Option<NonNull<Node<T>>>,
+    list: &'a mut LinkedList<T, A>,
+    index: Option<usize>,
+}
+
+impl<'a, T, A: NodeAllocator> CursoMut<'a, T, A> {
+    pub fn index(&self) -> Option<usize> {
+        self.index
+    }
+
+    // Read-only view at the same position, the mutable cursor can't be used while the view is alive
+    pub fn as_cursor(&self) -> Cursor<'_, T, A> {
+        Cursor {
+            cur: self.cur,
+            list: self.list,
+            index: self.index,
         }
     }
 
-    pub fn split_before(&mut self) -> LinkedList<T> {
+    // Mutable accessors need &mut self, so returned reference borrows the cursor
+    // -> can't hold two of them at the same time, or move cursor while holding one
+    // For shared access, use as_cursor() instead
+    pub fn current(&mut self) -> Option<&mut T> {
+        self.cur.map(|node| unsafe { &mut (*node.as_ptr()).val })
+    }
+
+    pub fn peek_next(&mut self) -> Option<&mut T> {
+        let next = unsafe { raw::next_of::<LinkedList<T, A>>(self.cur, self.list.head) };
+        next.map(|node| unsafe { &mut (*node.as_ptr()).val })
+    }
+
+    pub fn peek_prev(&mut self) -> Option<&mut T> {
+        let prev = unsafe { raw::prev_of::<LinkedList<T, A>>(self.cur, self.list.tail) };
+        prev.map(|node| unsafe { &mut (*node.as_ptr()).val })
+    }
+
+    pub fn move_next(&mut self) {
+        unsafe {
+            raw::move_next::<LinkedList<T, A>>(&mut self.cur, &mut self.index, self.list.head)
+        };
+    }
+
+    pub fn move_prev(&mut self) {
+        let (tail, len) = (self.list.tail, self.list.len);
+        unsafe { raw::move_prev::<LinkedList<T, A>>(&mut self.cur, &mut self.index, tail, len) };
+    }
+
+    pub fn split_before(&mut self) -> LinkedList<T, A> {
         // We have this:
         //
         //     list.front -> A <-> B <-> C <-> D <- list.back
@@ -488,38 +1668,19 @@ impl<'a, T> CursoMut<'a, T> {
         //
         //    return.front -> A <-> B <- return.back
         //
-        let (len, head, tail) = match (self.index.as_mut(), self.cur) {
-            (Some(index), Some(cur)) if self.list.len > 1 => unsafe {
-                let ret_tail = (*cur.as_ptr()).prev.take();
-                let ret_head = self.list.head.take();
-
-                if let Some(node) = ret_tail {
-                    (*node.as_ptr()).next = None;
-                }
-
-                self.list.head = Some(cur);
-                self.list.len -= *index + 1;
+        let mut ret = LinkedList::new_in(self.list.alloc.clone());
+        match (self.index.as_mut(), self.cur) {
+            (Some(index), Some(cur)) => unsafe {
+                self.list.split_before_node(cur, *index, &mut ret);
                 *index = 0;
-
-                (*index, ret_head, ret_tail)
             },
-            _ => {
-                let len = self.list.len;
-                self.list.len = 0;
-
-                (len, self.list.head.take(), self.list.tail.take())
-            }
-        };
-
-        LinkedList {
-            head,
-            tail,
-            len,
-            _phantom: PhantomData,
+            // Whole list is before ghost node
+            _ => ret.swap_nodes(self.list),
         }
+        ret
     }
 
-    pub fn split_after(&mut self) -> LinkedList<T> {
+    pub fn split_after(&mut self) -> LinkedList<T, A> {
         // We have this:
         //
         //     list.front -> A <-> B <-> C <-> D <- list.back
@@ -535,39 +1696,17 @@ impl<'a, T> CursoMut<'a, T> {
         //
         //
         //    return.front -> C <-> D <- return.back
-
-        let (len, head, tail) = match (self.index, self.cur) {
-            (Some(index), Some(cur)) if self.list.len > 1 => unsafe {
-                let ret_head = (*cur.as_ptr()).next.take();
-                let ret_tail = self.list.tail.take();
-
-                if let Some(node) = ret_head {
-                    (*node.as_ptr()).prev = None;
-                }
-
-                self.list.tail = Some(cur);
-                let ret_index = self.list.len - index - 1;
-                self.list.len = index + 1;
-
-                (ret_index, ret_head, ret_tail)
-            },
-            _ => {
-                let len = self.list.len;
-                self.list.len = 0;
-
-                (len, self.list.head.take(), self.list.tail.take())
-            }
-        };
-
-        LinkedList {
-            head,
-            tail,
-            len,
-            _phantom: PhantomData,
+        //
+        let mut ret = LinkedList::new_in(self.list.alloc.clone());
+        match (self.index, self.cur) {
+            (Some(index), Some(cur)) => unsafe { self.list.split_after_node(cur, index, &mut ret) },
+            // Whole list is after ghost node
+            _ => ret.swap_nodes(self.list),
         }
+        ret
     }
 
-    pub fn splice_before(&mut self, mut input: LinkedList<T>) {
+    pub fn splice_before(&mut self, mut input: LinkedList<T, A>) {
         // We have this:
         //
         // input.front -> 1 <-> 2 <- input.back
@@ -583,55 +1722,73 @@ impl<'a, T> CursoMut<'a, T> {
         //                                 ^
         //                                cur
         //
-        if input.is_empty() {
-            return;
+        // Before ghost node is the back of the list
+        self.list.assert_same_allocator(&input);
+        let len = input.len;
+        unsafe {
+            let prev = raw::prev_of::<LinkedList<T, A>>(self.cur, self.list.tail);
+            self.list.splice_between(&mut input, prev, self.cur);
         }
+        if let Some(index) = self.index.as_mut() {
+            *index += len;
+        }
+    }
 
-        match (self.index.as_mut(), self.cur) {
-            (Some(index), Some(cur)) => unsafe {
-                if let Some(prev) = (*cur.as_ptr()).prev {
-                    (*prev.as_ptr()).next = input.head;
-                    if let Some(input_head) = input.head.take() {
-                        (*input_head.as_ptr()).prev = Some(prev);
-                    }
-                } else {
-                    self.list.head = input.head.take();
-                }
-
-                (*cur.as_ptr()).prev = input.tail;
-                if let Some(input_tail) = input.tail.take() {
-                    (*input_tail.as_ptr()).next = Some(cur);
-                }
-
-                self.list.len += input.len;
-                *index += input.len;
-            },
-            // We are at pre-head (or ghost) node, like -1 index
-            _ => {
-                if let Some(old_tail) = self.list.tail.take() {
-                    unsafe {
-                        (*old_tail.as_ptr()).next = input.head;
-                        if let Some(input_head) = input.head.take() {
-                            (*input_head.as_ptr()).prev = Some(old_tail);
-                        }
-                    }
-                    self.list.tail = input.tail.take();
-                    self.list.len += input.len;
-                } else {
-                    self.list.head = input.head.take();
-                    self.list.tail = input.tail.take();
-                    self.list.len = input.len;
-                }
-            }
+    pub fn splice_after(&mut self, mut input: LinkedList<T, A>) {
+        // We have this:
+        //
+        // input.front -> 1 <-> 2 <- input.back
+        //
+        // list.front -> A <-> B <-> C <- list.back
+        //                     ^
+        //                    cur
+        //
+        //
+        // Becoming this:
+        //
+        // list.front -> A <-> B <-> 1 <-> 2 <-> C <- list.back
+        //                     ^
+        //                    cur
+        //
+        // After ghost node is the front of the list
+        self.list.assert_same_allocator(&input);
+        let len = input.len;
+        unsafe {
+            let next = raw::next_of::<LinkedList<T, A>>(self.cur, self.list.head);
+            self.list.splice_between(&mut input, self.cur, next);
+        }
+        if let Some(index) = self.index.as_mut() {
+            *index += len;
         }
-
-        input.len = 0;
     }
 
-    pub fn splice_after(&mut self, mut input: LinkedList<T>) {
+    pub fn insert_before(&mut self, val: T) {
         // We have this:
         //
-        // input.front -> 1 <-> 2 <- input.back
+        // list.front -> A <-> B <-> C <- list.back
+        //                     ^
+        //                    cur
+        //
+        //
+        // Becoming this:
+        //
+        // list.front -> A <-> 1 <-> B <-> C <- list.back
+        //                           ^
+        //                          cur
+        //
+        // Before ghost node is the back of the list
+        let node = self.list.alloc_node(val);
+        unsafe {
+            let prev = raw::prev_of::<LinkedList<T, A>>(self.cur, self.list.tail);
+            self.list.link_between(node, prev, self.cur);
+        }
+        if let Some(index) = self.index.as_mut() {
+            *index += 1;
+        }
+    }
+
+    pub fn insert_after(&mut self, val: T) {
+        // We have this:
         //
         // list.front -> A <-> B <-> C <- list.back
         //                     ^
@@ -640,70 +1797,118 @@ impl<'a, T> CursoMut<'a, T> {
         //
         // Becoming this:
         //
-        // list.front -> A <-> B <-> 1 <-> 2 <-> C <- list.back
+        // list.front -> A <-> B <-> 1 <-> C <- list.back
         //                     ^
         //                    cur
         //
-        if input.is_empty() {
-            return;
+        // After ghost node is the front of the list
+        let node = self.list.alloc_node(val);
+        unsafe {
+            let next = raw::next_of::<LinkedList<T, A>>(self.cur, self.list.head);
+            self.list.link_between(node, self.cur, next);
         }
+    }
 
-        match (self.index.as_mut(), self.cur) {
-            (Some(index), Some(cur)) => unsafe {
-                if let Some(next) = (*cur.as_ptr()).next {
-                    (*next.as_ptr()).prev = input.tail;
-                    if let Some(input_tail) = input.tail.take() {
-                        (*input_tail.as_ptr()).next = Some(next);
-                    }
-                } else {
-                    self.list.tail = input.tail.take();
-                }
+    pub fn remove_current(&mut self) -> Option<T> {
+        self.unlink_current().map(|node| unsafe {
+            // Node is already detached from the list, so we can take back ownership
+            self.list.free_node(node)
+        })
+    }
 
-                (*cur.as_ptr()).next = input.head;
-                if let Some(input_head) = input.head.take() {
-                    (*input_head.as_ptr()).prev = Some(cur);
-                }
+    pub fn remove_current_as_list(&mut self) -> Option<LinkedList<T, A>> {
+        // Reuse detached node as a single element list, so no reallocation is needed
+        self.unlink_current().map(|node| LinkedList {
+            head: Some(node),
+            tail: Some(node),
+            len: 1,
+            pool: None,
+            alloc: self.list.alloc.clone(),
+            _phantom: PhantomData,
+        })
+    }
 
-                self.list.len += input.len;
-                *index += input.len;
-            },
-            // Cursor is at pre-head (or ghost) node, like -1 index
-            _ => {
-                if let Some(old_head) = self.list.head.take() {
-                    unsafe {
-                        (*old_head.as_ptr()).prev = input.tail;
-                        if let Some(input_tail) = input.tail.take() {
-                            (*input_tail.as_ptr()).next = Some(old_head);
-                        }
-                    }
+    fn unlink_current(&mut self) -> Option<NonNull<Node<T>>> {
+        // We have this:
+        //
+        //     list.front -> A <-> B <-> C <- list.back
+        //                         ^
+        //                        cur
+        //
+        //
+        // And we want to produce this:
+        //
+        //     list.front -> A <-> C <- list.back
+        //                         ^
+        //                        cur
+        //
+        //
+        //    return -> B
+        //
+        // Cursor at ghost node has nothing to remove
+        let cur = self.cur?;
 
-                    self.list.head = input.head.take();
-                    self.list.len += input.len;
-                } else {
-                    self.list.head = input.head.take();
-                    self.list.tail = input.tail.take();
-                    self.list.len = input.len;
-                }
+        unsafe {
+            // Next node takes over current index, if removed node is tail, we move to ghost node
+            self.cur = (*cur.as_ptr()).next;
+            if self.cur.is_none() {
+                self.index = None;
             }
+
+            self.list.unlink_node(cur);
         }
 
-        input.len = 0;
+        Some(cur)
     }
 }
 
-impl<T> LinkedList<T> {
-    pub fn cursor_mut(&mut self) -> CursoMut<T> {
+impl<T, A: NodeAllocator> LinkedList<T, A> {
+    pub fn cursor_mut(&mut self) -> CursoMut<'_, T, A> {
         CursoMut {
             cur: None,
             list: self,
             index: None,
         }
     }
+
+    pub fn cursor_front(&self) -> Cursor<'_, T, A> {
+        Cursor {
+            cur: self.head,
+            list: self,
+            index: self.head.map(|_| 0),
+        }
+    }
+
+    pub fn cursor_back(&self) -> Cursor<'_, T, A> {
+        Cursor {
+            cur: self.tail,
+            list: self,
+            index: self.tail.map(|_| self.len - 1),
+        }
+    }
+
+    pub fn cursor_front_mut(&mut self) -> CursoMut<'_, T, A> {
+        CursoMut {
+            cur: self.head,
+            index: self.head.map(|_| 0),
+            list: self,
+        }
+    }
+
+    pub fn cursor_back_mut(&mut self) -> CursoMut<'_, T, A> {
+        CursoMut {
+            cur: self.tail,
+            index: self.tail.map(|_| self.len - 1),
+            list: self,
+        }
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use std::cell::Cell;
+    use std::rc::Rc;
 
     #[test]
     fn test() {
@@ -1019,6 +2224,30 @@ mod tests {
 
         assert!(map.is_empty());
     }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_serde() {
+        use crate::test_serializer::{from_value, to_value, Value};
+
+        let list: LinkedList<i32> = (0..5).collect();
+        let value = to_value(&list).unwrap();
+        assert_eq!(value, Value::Seq((0..5).map(Value::Int).collect()));
+        let back: LinkedList<i32> = from_value(value).unwrap();
+        check_links(&back);
+        assert_eq!(back, list);
+
+        // Nested lists keep their order too
+        let nested: LinkedList<LinkedList<String>> = [vec!["a", "b"], vec![], vec!["c"]]
+            .into_iter()
+            .map(|v| v.into_iter().map(String::from).collect())
+            .collect();
+        let back: LinkedList<LinkedList<String>> = from_value(to_value(&nested).unwrap()).unwrap();
+        assert_eq!(back, nested);
+
+        assert!(from_value::<LinkedList<i32>>(Value::Str("x".into())).is_err());
+        assert!(from_value::<LinkedList<i32>>(Value::Seq(vec![Value::Str("x".into())])).is_err());
+    }
     #[test]
     fn test_cursor_move_peek() {
         let mut m: LinkedList<u32> = LinkedList::new();
@@ -1026,40 +2255,99 @@ mod tests {
         let mut cursor = m.cursor_mut();
         cursor.move_next();
         assert_eq!(cursor.current(), Some(&mut 1));
-        assert_eq!(cursor.next(), Some(&mut 2));
-        assert_eq!(cursor.previous(), None);
+        assert_eq!(cursor.peek_next(), Some(&mut 2));
+        assert_eq!(cursor.peek_prev(), None);
         assert_eq!(cursor.index(), Some(0));
         cursor.move_prev();
         assert_eq!(cursor.current(), None);
-        assert_eq!(cursor.next(), Some(&mut 1));
-        assert_eq!(cursor.previous(), Some(&mut 6));
+        assert_eq!(cursor.peek_next(), Some(&mut 1));
+        assert_eq!(cursor.peek_prev(), Some(&mut 6));
         assert_eq!(cursor.index(), None);
         cursor.move_next();
         cursor.move_next();
         assert_eq!(cursor.current(), Some(&mut 2));
-        assert_eq!(cursor.next(), Some(&mut 3));
-        assert_eq!(cursor.previous(), Some(&mut 1));
+        assert_eq!(cursor.peek_next(), Some(&mut 3));
+        assert_eq!(cursor.peek_prev(), Some(&mut 1));
         assert_eq!(cursor.index(), Some(1));
 
         let mut cursor = m.cursor_mut();
         cursor.move_prev();
         assert_eq!(cursor.current(), Some(&mut 6));
-        assert_eq!(cursor.next(), None);
-        assert_eq!(cursor.previous(), Some(&mut 5));
+        assert_eq!(cursor.peek_next(), None);
+        assert_eq!(cursor.peek_prev(), Some(&mut 5));
         assert_eq!(cursor.index(), Some(5));
         cursor.move_next();
         assert_eq!(cursor.current(), None);
-        assert_eq!(cursor.next(), Some(&mut 1));
-        assert_eq!(cursor.previous(), Some(&mut 6));
+        assert_eq!(cursor.peek_next(), Some(&mut 1));
+        assert_eq!(cursor.peek_prev(), Some(&mut 6));
         assert_eq!(cursor.index(), None);
         cursor.move_prev();
         cursor.move_prev();
         assert_eq!(cursor.current(), Some(&mut 5));
-        assert_eq!(cursor.next(), Some(&mut 6));
-        assert_eq!(cursor.previous(), Some(&mut 4));
+        assert_eq!(cursor.peek_next(), Some(&mut 6));
+        assert_eq!(cursor.peek_prev(), Some(&mut 4));
         assert_eq!(cursor.index(), Some(4));
     }
 
+    #[test]
+    fn test_cursor_front_back() {
+        let mut m: LinkedList<u32> = LinkedList::new();
+        m.extend([1, 2, 3, 4, 5, 6]);
+        let mut cursor = m.cursor_front();
+        assert_eq!(cursor.current(), Some(&1));
+        assert_eq!(cursor.peek_next(), Some(&2));
+        assert_eq!(cursor.peek_prev(), None);
+        assert_eq!(cursor.index(), Some(0));
+        cursor.move_prev();
+        assert_eq!(cursor.current(), None);
+        assert_eq!(cursor.peek_next(), Some(&1));
+        assert_eq!(cursor.peek_prev(), Some(&6));
+        assert_eq!(cursor.index(), None);
+        cursor.move_next();
+        cursor.move_next();
+        assert_eq!(cursor.current(), Some(&2));
+        assert_eq!(cursor.peek_next(), Some(&3));
+        assert_eq!(cursor.peek_prev(), Some(&1));
+        assert_eq!(cursor.index(), Some(1));
+
+        let mut cursor = m.cursor_back();
+        assert_eq!(cursor.current(), Some(&6));
+        assert_eq!(cursor.peek_next(), None);
+        assert_eq!(cursor.peek_prev(), Some(&5));
+        assert_eq!(cursor.index(), Some(5));
+        cursor.move_next();
+        assert_eq!(cursor.current(), None);
+        assert_eq!(cursor.peek_next(), Some(&1));
+        assert_eq!(cursor.peek_prev(), Some(&6));
+        assert_eq!(cursor.index(), None);
+        cursor.move_prev();
+        cursor.move_prev();
+        assert_eq!(cursor.current(), Some(&5));
+        assert_eq!(cursor.peek_next(), Some(&6));
+        assert_eq!(cursor.peek_prev(), Some(&4));
+        assert_eq!(cursor.index(), Some(4));
+
+        let mut cursor = m.cursor_front_mut();
+        assert_eq!(cursor.index(), Some(0));
+        *cursor.current().unwrap() = 10;
+        cursor.move_next();
+        let view = cursor.as_cursor();
+        assert_eq!(view.current(), Some(&2));
+        assert_eq!(view.peek_prev(), Some(&10));
+        assert_eq!(view.index(), Some(1));
+
+        let mut cursor = m.cursor_back_mut();
+        assert_eq!(cursor.index(), Some(5));
+        assert_eq!(cursor.remove_current(), Some(6));
+        assert_eq!(m.back(), Some(&5));
+
+        let mut empty: LinkedList<u32> = LinkedList::new();
+        assert_eq!(empty.cursor_front().current(), None);
+        assert_eq!(empty.cursor_back().index(), None);
+        assert_eq!(empty.cursor_front_mut().index(), None);
+        assert_eq!(empty.cursor_back_mut().current(), None);
+    }
+
     #[test]
     fn test_cursor_mut_insert() {
         let mut m: LinkedList<u32> = LinkedList::new();
@@ -1084,7 +2372,6 @@ mod tests {
             &[10, 7, 1, 8, 2, 3, 4, 5, 6, 9]
         );
 
-        /* remove_current not implemented
         let mut cursor = m.cursor_mut();
         cursor.move_next();
         cursor.move_prev();
@@ -1099,14 +2386,10 @@ mod tests {
         cursor.move_next();
         assert_eq!(cursor.remove_current(), Some(10));
         check_links(&m);
-        assert_eq!(m.iter().cloned().collect::<Vec<_>>(), &[1, 8, 2, 3, 4, 5, 6]);
-        */
-
-        // Because remove_current is not implemented
-        // We need to take above commented block results
-        m = LinkedList::new();
-        m.extend([1, 8, 2, 3, 4, 5, 6]);
-        //
+        assert_eq!(
+            m.iter().cloned().collect::<Vec<_>>(),
+            &[1, 8, 2, 3, 4, 5, 6]
+        );
 
         let mut cursor = m.cursor_mut();
         cursor.move_next();
@@ -1151,11 +2434,1105 @@ mod tests {
         );
     }
 
-    fn check_links<T: Eq + Debug>(list: &LinkedList<T>) {
+    #[test]
+    fn test_cursor_split() {
+        let mut m: LinkedList<u32> = (0..5).collect();
+        let mut cursor = m.cursor_front_mut();
+        cursor.move_next();
+        cursor.move_next();
+        let front = cursor.split_before();
+        assert_eq!(cursor.index(), Some(0));
+        assert_eq!(cursor.current(), Some(&mut 2));
+        check_links(&front);
+        check_links(&m);
+        assert_eq!(front.iter().copied().collect::<Vec<_>>(), &[0, 1]);
+        assert_eq!(m.iter().copied().collect::<Vec<_>>(), &[2, 3, 4]);
+
+        // Nothing before front, nothing after back, even with one element
+        let mut one: LinkedList<u32> = (0..1).collect();
+        assert!(one.cursor_front_mut().split_before().is_empty());
+        assert!(one.cursor_back_mut().split_after().is_empty());
+        check_links(&one);
+        assert_eq!(one.len(), 1);
+    }
+
+    #[test]
+    fn test_cursor_remove_current_as_list() {
+        let mut m: LinkedList<u32> = LinkedList::new();
+        m.extend([1, 2, 3]);
+        let mut cursor = m.cursor_mut();
+        assert!(cursor.remove_current_as_list().is_none());
+        cursor.move_prev();
+        let removed = cursor.remove_current_as_list().unwrap();
+        assert_eq!(cursor.index(), None);
+        assert_eq!(cursor.current(), None);
+        check_links(&removed);
+        assert_eq!(removed.into_iter().collect::<Vec<_>>(), &[3]);
+        cursor.move_next();
+        let removed = cursor.remove_current_as_list().unwrap();
+        assert_eq!(cursor.index(), Some(0));
+        assert_eq!(cursor.current(), Some(&mut 2));
+        assert_eq!(removed.into_iter().collect::<Vec<_>>(), &[1]);
+        assert_eq!(cursor.remove_current(), Some(2));
+        assert_eq!(cursor.index(), None);
+        assert_eq!(cursor.remove_current(), None);
+        assert_eq!(m.len(), 0);
+        assert_eq!(m.front(), None);
+        assert_eq!(m.back(), None);
+    }
+
+    #[test]
+    fn test_cursor_mut_insert_single() {
+        let mut m: LinkedList<u32> = LinkedList::new();
+        let mut cursor = m.cursor_mut();
+        cursor.insert_before(1);
+        cursor.insert_after(0);
+        cursor.insert_before(2);
+        assert_eq!(cursor.index(), None);
+        check_links(&m);
+        assert_eq!(m.iter().cloned().collect::<Vec<_>>(), &[0, 1, 2]);
+
+        let mut cursor = m.cursor_mut();
+        cursor.move_next();
+        cursor.insert_before(7);
+        assert_eq!(cursor.index(), Some(1));
+        assert_eq!(cursor.current(), Some(&mut 0));
+        cursor.insert_after(8);
+        assert_eq!(cursor.index(), Some(1));
+        assert_eq!(cursor.peek_next(), Some(&mut 8));
+        cursor.move_prev();
+        cursor.move_prev();
+        cursor.move_prev();
+        assert_eq!(cursor.current(), Some(&mut 2));
+        cursor.insert_after(9);
+        assert_eq!(cursor.index(), Some(4));
+        check_links(&m);
+        assert_eq!(m.len(), 6);
+        assert_eq!(m.iter().cloned().collect::<Vec<_>>(), &[7, 0, 8, 1, 2, 9]);
+        assert_eq!(m.back(), Some(&9));
+    }
+
+    #[test]
+    fn test_send() {
+        let list: LinkedList<i32> = (0..10).collect();
+        let handle = std::thread::spawn(move || list.into_iter().sum::<i32>());
+        assert_eq!(handle.join().unwrap(), 45);
+
+        let mut list: LinkedList<i32> = (0..10).collect();
+        std::thread::scope(|s| {
+            let mut iter = list.iter_mut();
+            s.spawn(move || iter.next().map(|x| *x = 100));
+        });
+        let list = &list;
+        std::thread::scope(|s| {
+            s.spawn(|| assert_eq!(list.iter().sum::<i32>(), 145));
+            s.spawn(|| assert_eq!(list.cursor_back().current(), Some(&9)));
+        });
+    }
+
+    // Only need to compile, these never run
+    #[allow(dead_code)]
+    fn assert_properties() {
+        fn is_send<T: Send>() {}
+        fn is_sync<T: Sync>() {}
+
+        is_send::<LinkedList<i32>>();
+        is_sync::<LinkedList<i32>>();
+
+        is_send::<IntoIter<i32>>();
+        is_sync::<IntoIter<i32>>();
+
+        is_send::<Iter<'static, i32>>();
+        is_sync::<Iter<'static, i32>>();
+
+        is_send::<IterMut<'static, i32>>();
+        is_sync::<IterMut<'static, i32>>();
+
+        is_send::<ExtractIf<'static, i32, fn(&mut i32) -> bool>>();
+        is_sync::<ExtractIf<'static, i32, fn(&mut i32) -> bool>>();
+
+        is_send::<Drain<'static, i32>>();
+        is_sync::<Drain<'static, i32>>();
+
+        is_send::<Cursor<'static, i32>>();
+        is_sync::<Cursor<'static, i32>>();
+
+        is_send::<CursoMut<'static, i32>>();
+        is_sync::<CursoMut<'static, i32>>();
+
+        // Shrinking lifetime of items must be accepted, like std collections
+        fn linked_list_covariant<'a, T>(x: LinkedList<&'static T>) -> LinkedList<&'a T> {
+            x
+        }
+        fn iter_covariant<'i, 'a, T>(x: Iter<'i, &'static T>) -> Iter<'i, &'a T> {
+            x
+        }
+        fn into_iter_covariant<'a, T>(x: IntoIter<&'static T>) -> IntoIter<&'a T> {
+            x
+        }
+    }
+
+    #[test]
+    fn test_append() {
+        let mut m: LinkedList<i32> = LinkedList::new();
+        let mut n: LinkedList<i32> = LinkedList::new();
+        m.append(&mut n);
+        check_links(&m);
+        check_links(&n);
+        assert!(m.is_empty());
+
+        let mut n = list_from(&[1, 2, 3]);
+        m.append(&mut n);
+        check_links(&m);
+        check_links(&n);
+        assert_eq!(m.len(), 3);
+        assert_eq!(n.len(), 0);
+
+        let mut n = list_from(&[4, 5]);
+        m.append(&mut n);
+        check_links(&m);
+        check_links(&n);
+        assert_eq!(m.iter().cloned().collect::<Vec<_>>(), &[1, 2, 3, 4, 5]);
+        assert!(n.is_empty());
+
+        n.push_back(9);
+        m.append(&mut LinkedList::new());
+        n.append(&mut m);
+        check_links(&m);
+        check_links(&n);
+        assert_eq!(n.iter().cloned().collect::<Vec<_>>(), &[9, 1, 2, 3, 4, 5]);
+        assert!(m.is_empty());
+    }
+
+    #[test]
+    fn test_prepend() {
+        let mut m = list_from(&[3, 4]);
+        let mut n = list_from(&[1, 2]);
+        m.prepend(&mut n);
+        check_links(&m);
+        check_links(&n);
+        assert_eq!(m.iter().cloned().collect::<Vec<_>>(), &[1, 2, 3, 4]);
+        assert!(n.is_empty());
+
+        m.prepend(&mut n);
+        check_links(&m);
+        assert_eq!(m.len(), 4);
+
+        n.prepend(&mut m);
+        check_links(&m);
+        check_links(&n);
+        assert_eq!(n.iter().cloned().collect::<Vec<_>>(), &[1, 2, 3, 4]);
+        assert!(m.is_empty());
+    }
+
+    #[test]
+    fn test_split_off() {
+        for len in 0..8 {
+            for at in 0..=len {
+                let mut m: LinkedList<i32> = (0..len).collect();
+                let n = m.split_off(at as usize);
+                check_links(&m);
+                check_links(&n);
+                assert_eq!(
+                    m.iter().cloned().collect::<Vec<_>>(),
+                    (0..at).collect::<Vec<_>>()
+                );
+                assert_eq!(
+                    n.iter().cloned().collect::<Vec<_>>(),
+                    (at..len).collect::<Vec<_>>()
+                );
+            }
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "Cannot split off at a nonexistent index")]
+    fn test_split_off_out_of_range() {
+        let mut m = list_from(&[1, 2, 3]);
+        m.split_off(4);
+    }
+
+    #[test]
+    fn test_retain() {
+        let mut m: LinkedList<i32> = (0..10).collect();
+        let survivors: Vec<_> = m
+            .iter()
+            .filter(|x| *x % 3 == 0)
+            .map(|x| x as *const i32)
+            .collect();
+        m.retain(|x| x % 3 == 0);
+        check_links(&m);
+        assert_eq!(m.iter().cloned().collect::<Vec<_>>(), &[0, 3, 6, 9]);
+        // Survivors are relinked in place, not moved
+        assert_eq!(
+            m.iter().map(|x| x as *const i32).collect::<Vec<_>>(),
+            survivors
+        );
+
+        m.retain_mut(|x| {
+            *x += 1;
+            *x > 4
+        });
+        check_links(&m);
+        assert_eq!(m.iter().cloned().collect::<Vec<_>>(), &[7, 10]);
+
+        m.retain(|_| false);
+        check_links(&m);
+        assert!(m.is_empty());
+    }
+
+    #[test]
+    fn test_extract_if() {
+        let mut m: LinkedList<i32> = (0..10).collect();
+        {
+            let mut iter = m.extract_if(|x| *x % 2 == 1);
+            assert_eq!(iter.size_hint(), (0, Some(10)));
+            assert_eq!(iter.next(), Some(1));
+            assert_eq!(iter.next(), Some(3));
+            assert_eq!(iter.size_hint(), (0, Some(6)));
+            // Drop early, unvisited elements stay
+        }
+        check_links(&m);
+        assert_eq!(
+            m.iter().cloned().collect::<Vec<_>>(),
+            &[0, 2, 4, 5, 6, 7, 8, 9]
+        );
+
+        let extracted: Vec<_> = m.extract_if(|x| *x > 4).collect();
+        check_links(&m);
+        assert_eq!(extracted, &[5, 6, 7, 8, 9]);
+        assert_eq!(m.iter().cloned().collect::<Vec<_>>(), &[0, 2, 4]);
+
+        let mut empty: LinkedList<i32> = LinkedList::new();
+        assert_eq!(empty.extract_if(|_| true).next(), None);
+    }
+
+    #[test]
+    fn test_extract_if_pred_panic() {
+        let mut m: LinkedList<i32> = (0..10).collect();
+        let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            m.retain(|x| {
+                assert!(*x != 6);
+                x % 2 == 0
+            })
+        }));
+        assert!(result.is_err());
+        check_links(&m);
+        assert_eq!(m.len(), 7);
+        assert_eq!(
+            m.iter().cloned().collect::<Vec<_>>(),
+            &[0, 2, 4, 6, 7, 8, 9]
+        );
+    }
+
+    #[test]
+    fn test_dedup() {
+        let mut m = list_from(&[1, 1, 2, 3, 3, 3, 1, 4, 4]);
+        let nodes: Vec<_> = {
+            let mut cursor = m.cursor_front();
+            let mut nodes = vec![];
+            while let Some(val) = cursor.current() {
+                nodes.push(val as *const i32);
+                cursor.move_next();
+            }
+            nodes
+        };
+        assert_eq!(m.dedup(), 4);
+        check_links(&m);
+        assert_eq!(m.iter().copied().collect::<Vec<_>>(), &[1, 2, 3, 1, 4]);
+
+        // Survivors are the first node of each run, still at the same address
+        let kept: Vec<_> = m.iter().map(|val| val as *const i32).collect();
+        assert_eq!(kept, [nodes[0], nodes[2], nodes[3], nodes[6], nodes[7]]);
+
+        assert_eq!(m.dedup(), 0);
+        let mut empty: LinkedList<i32> = LinkedList::new();
+        assert_eq!(empty.dedup(), 0);
+        let mut same = list_from(&[7, 7, 7]);
+        assert_eq!(same.dedup(), 2);
+        check_links(&same);
+        assert_eq!(same.iter().copied().collect::<Vec<_>>(), &[7]);
+    }
+
+    #[test]
+    fn test_dedup_by() {
+        let mut m: LinkedList<i32> = [10, 11, 20, 25, 29, 31].into_iter().collect();
+        assert_eq!(m.dedup_by_key(|x| *x / 10), 3);
+        check_links(&m);
+        assert_eq!(m.iter().copied().collect::<Vec<_>>(), &[10, 20, 31]);
+
+        // Candidate comes first, last kept second, so duplicates can be folded into survivor
+        let mut m: LinkedList<(char, i32)> = [('a', 1), ('a', 2), ('b', 3), ('b', 4), ('a', 5)]
+            .into_iter()
+            .collect();
+        let removed = m.dedup_by(|dup, kept| {
+            let same = dup.0 == kept.0;
+            if same {
+                kept.1 += dup.1;
+            }
+            same
+        });
+        assert_eq!(removed, 2);
+        check_links(&m);
+        assert_eq!(
+            m.iter().copied().collect::<Vec<_>>(),
+            &[('a', 3), ('b', 7), ('a', 5)]
+        );
+    }
+
+    #[test]
+    fn test_dedup_drop_panic() {
+        let bombs = Bombs::new(0);
+        let alloc = CountingAlloc::default();
+        let mut m = LinkedList::new_in(alloc.clone());
+        for i in 0..5 {
+            m.push_back((i / 2, bombs.make(i == 1)));
+        }
+
+        let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            m.dedup_by_key(|(key, _)| *key)
+        }));
+        assert!(result.is_err());
+        assert_eq!(bombs.drops.get(), 1);
+        assert_eq!(alloc.live(), 4);
+        assert_eq!(
+            m.iter().map(|(key, _)| *key).collect::<Vec<_>>(),
+            &[0, 1, 1, 2]
+        );
+
+        drop(m);
+        assert_eq!(bombs.drops.get(), 5);
+        assert_eq!(alloc.live(), 0);
+    }
+
+    #[test]
+    fn test_sort() {
+        // Simple LCG, so test doesn't need a rand crate
+        let mut seed = 42u32;
+        let mut rand = move || {
+            seed = seed.wrapping_mul(1103515245).wrapping_add(12345);
+            (seed >> 16) % 100
+        };
+
+        for len in 0..40 {
+            let v: Vec<u32> = (0..len).map(|_| rand()).collect();
+            let mut m: LinkedList<u32> = v.iter().cloned().collect();
+            let mut nodes: Vec<_> = m.iter().map(|x| x as *const u32).collect();
+
+            m.sort();
+            check_links(&m);
+            assert!(m.is_sorted());
+
+            let mut sorted = v.clone();
+            sorted.sort();
+            assert_eq!(m.iter().cloned().collect::<Vec<_>>(), sorted);
+
+            // Same nodes are relinked, nothing is reallocated
+            let mut relinked: Vec<_> = m.iter().map(|x| x as *const u32).collect();
+            nodes.sort();
+            relinked.sort();
+            assert_eq!(nodes, relinked);
+
+            m.sort_by(|a, b| b.cmp(a));
+            check_links(&m);
+            sorted.reverse();
+            assert_eq!(m.iter().cloned().collect::<Vec<_>>(), sorted);
+        }
+    }
+
+    #[test]
+    fn test_sort_stable() {
+        let mut m: LinkedList<(u32, u32)> = (0..50).map(|i| ((i * 7) % 5, i)).collect();
+        m.sort_by_key(|&(key, _)| key);
+        check_links(&m);
+
+        let mut v: Vec<_> = (0..50).map(|i| ((i * 7) % 5, i)).collect();
+        v.sort_by_key(|&(key, _)| key);
+        assert_eq!(m.iter().cloned().collect::<Vec<_>>(), v);
+    }
+
+    #[test]
+    fn test_is_sorted() {
+        assert!(LinkedList::<i32>::new().is_sorted());
+        assert!(list_from(&[1]).is_sorted());
+        assert!(list_from(&[1, 1, 2, 3]).is_sorted());
+        assert!(!list_from(&[1, 3, 2]).is_sorted());
+        assert!(!list_from(&[1.0, f64::NAN]).is_sorted());
+    }
+
+    #[test]
+    fn test_sort_panic() {
+        let mut m: LinkedList<i32> = (0..20).rev().collect();
+        let mut count = 0;
+        let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            m.sort_by(|a, b| {
+                count += 1;
+                assert!(count < 30);
+                a.cmp(b)
+            })
+        }));
+        assert!(result.is_err());
+        check_links(&m);
+
+        // Every element is still in the list
+        let mut v: Vec<_> = m.iter().cloned().collect();
+        v.sort();
+        assert_eq!(v, (0..20).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn test_merge() {
+        let mut m = list_from(&[1, 3, 5, 7]);
+        let mut n = list_from(&[0, 2, 3, 8, 9]);
+        m.merge(&mut n);
+        check_links(&m);
+        check_links(&n);
+        assert!(n.is_empty());
+        assert_eq!(
+            m.iter().cloned().collect::<Vec<_>>(),
+            &[0, 1, 2, 3, 3, 5, 7, 8, 9]
+        );
+
+        let mut empty = LinkedList::new();
+        m.merge(&mut empty);
+        check_links(&m);
+        assert_eq!(m.len(), 9);
+        empty.merge(&mut m);
+        check_links(&empty);
+        assert_eq!(empty.len(), 9);
+        assert!(m.is_empty());
+
+        // Equal elements of self come first
+        let mut m: LinkedList<(u32, char)> = list_from(&[(1, 'a'), (2, 'a')]);
+        let mut n = list_from(&[(1, 'b'), (2, 'b')]);
+        m.merge_by(&mut n, |a, b| a.0.cmp(&b.0));
+        check_links(&m);
+        assert_eq!(
+            m.iter().cloned().collect::<Vec<_>>(),
+            &[(1, 'a'), (1, 'b'), (2, 'a'), (2, 'b')]
+        );
+    }
+
+    #[test]
+    fn test_drain() {
+        for len in 0..6 {
+            for start in 0..=len {
+                for end in start..=len {
+                    let mut m: LinkedList<i32> = (0..len).collect();
+                    let drained: Vec<_> = m.drain(start as usize..end as usize).collect();
+                    check_links(&m);
+                    assert_eq!(drained, (start..end).collect::<Vec<_>>());
+                    assert_eq!(
+                        m.iter().cloned().collect::<Vec<_>>(),
+                        (0..start).chain(end..len).collect::<Vec<_>>()
+                    );
+                }
+            }
+        }
+
+        let mut m: LinkedList<i32> = (0..10).collect();
+        assert_eq!(m.drain(..).count(), 10);
+        check_links(&m);
+        assert!(m.is_empty());
+
+        let mut m: LinkedList<i32> = (0..10).collect();
+        assert_eq!(m.drain(7..).collect::<Vec<_>>(), &[7, 8, 9]);
+        assert_eq!(m.drain(..=1).collect::<Vec<_>>(), &[0, 1]);
+        assert_eq!(
+            m.drain((Bound::Excluded(0), Bound::Included(2)))
+                .collect::<Vec<_>>(),
+            &[3, 4]
+        );
+        check_links(&m);
+        assert_eq!(m.iter().cloned().collect::<Vec<_>>(), &[2, 5, 6]);
+    }
+
+    #[test]
+    fn test_drain_double_end() {
+        let mut m: LinkedList<i32> = (0..10).collect();
+        let mut drain = m.drain(2..8);
+        assert_eq!(drain.len(), 6);
+        assert_eq!(drain.next(), Some(2));
+        assert_eq!(drain.next_back(), Some(7));
+        assert_eq!(drain.next_back(), Some(6));
+        assert_eq!(drain.size_hint(), (3, Some(3)));
+        assert_eq!(drain.next(), Some(3));
+        assert_eq!(drain.rev().collect::<Vec<_>>(), &[5, 4]);
+        check_links(&m);
+        assert_eq!(m.iter().cloned().collect::<Vec<_>>(), &[0, 1, 8, 9]);
+    }
+
+    #[test]
+    fn test_drain_partial_and_leak() {
+        let mut m: LinkedList<i32> = (0..10).collect();
+        {
+            let mut drain = m.drain(3..6);
+            assert_eq!(drain.next(), Some(3));
+        }
+        check_links(&m);
+        assert_eq!(
+            m.iter().cloned().collect::<Vec<_>>(),
+            &[0, 1, 2, 6, 7, 8, 9]
+        );
+
+        mem::forget(m.drain(1..3));
+        check_links(&m);
+        assert_eq!(m.iter().cloned().collect::<Vec<_>>(), &[0, 6, 7, 8, 9]);
+    }
+
+    #[test]
+    #[should_panic(expected = "drain end 4 is out of range for list of length 3")]
+    fn test_drain_out_of_range() {
+        let mut m = list_from(&[1, 2, 3]);
+        m.drain(1..4);
+    }
+
+    #[test]
+    #[should_panic(expected = "drain start 2 is greater than end 1")]
+    #[allow(clippy::reversed_empty_ranges)]
+    fn test_drain_reversed_range() {
+        let mut m = list_from(&[1, 2, 3]);
+        m.drain(2..1);
+    }
+
+    #[test]
+    fn test_get() {
+        let mut m: LinkedList<i32> = (0..7).collect();
+        for i in 0..7 {
+            assert_eq!(m.get(i), Some(&(i as i32)));
+            assert_eq!(m[i], i as i32);
+        }
+        assert_eq!(m.get(7), None);
+        assert_eq!(m.get_mut(7), None);
+
+        *m.get_mut(1).unwrap() = 10;
+        m[5] += 10;
+        check_links(&m);
+        assert_eq!(
+            m.iter().cloned().collect::<Vec<_>>(),
+            &[0, 10, 2, 3, 4, 15, 6]
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "index out of bounds: the len is 3 but the index is 3")]
+    fn test_index_out_of_range() {
+        let m = list_from(&[1, 2, 3]);
+        let _ = m[3];
+    }
+
+    #[test]
+    fn test_insert_remove() {
+        let mut m: LinkedList<i32> = LinkedList::new();
+        m.insert(0, 1);
+        m.insert(0, 0);
+        m.insert(2, 3);
+        m.insert(2, 2);
+        m.insert(4, 4);
+        check_links(&m);
+        assert_eq!(m.iter().cloned().collect::<Vec<_>>(), &[0, 1, 2, 3, 4]);
+
+        assert_eq!(m.remove(5), None);
+        assert_eq!(m.remove(4), Some(4));
+        assert_eq!(m.remove(0), Some(0));
+        assert_eq!(m.remove(1), Some(2));
+        check_links(&m);
+        assert_eq!(m.iter().cloned().collect::<Vec<_>>(), &[1, 3]);
+
+        let err = m.try_insert(3, 9).unwrap_err();
+        assert_eq!(err.error(), IndexError::new(3, 2));
+        assert_eq!(err.into_inner(), 9);
+        assert_eq!(m.try_insert(1, 2), Ok(()));
+
+        let err = m.try_remove(3).unwrap_err();
+        assert_eq!((err.index, err.len), (3, 3));
+        assert_eq!(
+            err.to_string(),
+            "index out of bounds: the len is 3 but the index is 3"
+        );
+        assert_eq!(m.try_remove(2), Ok(3));
+        check_links(&m);
+        assert_eq!(m.iter().cloned().collect::<Vec<_>>(), &[1, 2]);
+    }
+
+    #[test]
+    #[should_panic(expected = "index out of bounds: the len is 3 but the index is 4")]
+    fn test_insert_out_of_range() {
+        let mut m = list_from(&[1, 2, 3]);
+        m.insert(4, 4);
+    }
+
+    #[test]
+    fn test_swap() {
+        let mut m: LinkedList<i32> = (0..7).collect();
+        m.swap(0, 6);
+        m.swap(2, 4);
+        m.swap(3, 3);
+        check_links(&m);
+        assert_eq!(
+            m.iter().cloned().collect::<Vec<_>>(),
+            &[6, 1, 4, 3, 2, 5, 0]
+        );
+    }
+
+    #[test]
+    fn test_reverse() {
+        let alloc = CountingAlloc::default();
+        let mut m = LinkedList::new_in(alloc.clone());
+        m.reverse();
+        check_links(&m);
+        assert!(m.is_empty());
+
+        m.push_back(1);
+        m.reverse();
+        check_links(&m);
+        assert_eq!(m.iter().copied().collect::<Vec<_>>(), &[1]);
+
+        m.extend(2..6);
+        let before = alloc.allocations();
+        m.reverse();
+        assert_eq!(alloc.allocations(), before);
+        check_links(&m);
+        assert_eq!(m.iter().copied().collect::<Vec<_>>(), &[5, 4, 3, 2, 1]);
+
+        // Cursors and push/pop keep working on the flipped links
+        m.push_front(6);
+        m.push_back(0);
+        let mut cursor = m.cursor_back_mut();
+        cursor.move_prev();
+        cursor.insert_after(7);
+        check_links(&m);
+        assert_eq!(
+            m.iter().copied().collect::<Vec<_>>(),
+            &[6, 5, 4, 3, 2, 1, 7, 0]
+        );
+    }
+
+    #[test]
+    fn test_rotate() {
+        let mut m: LinkedList<i32> = (0..6).collect();
+        m.rotate_left(2);
+        check_links(&m);
+        assert_eq!(m.iter().copied().collect::<Vec<_>>(), &[2, 3, 4, 5, 0, 1]);
+
+        m.rotate_right(2);
+        check_links(&m);
+        assert_eq!(m.iter().copied().collect::<Vec<_>>(), &[0, 1, 2, 3, 4, 5]);
+
+        // Split point closer to the back
+        m.rotate_left(5);
+        check_links(&m);
+        assert_eq!(m.iter().copied().collect::<Vec<_>>(), &[5, 0, 1, 2, 3, 4]);
+
+        m.rotate_right(1);
+        check_links(&m);
+        assert_eq!(m.iter().copied().collect::<Vec<_>>(), &[4, 5, 0, 1, 2, 3]);
+
+        for n in [0, 6] {
+            m.rotate_left(n);
+            m.rotate_right(n);
+        }
+        check_links(&m);
+        assert_eq!(m.iter().copied().collect::<Vec<_>>(), &[4, 5, 0, 1, 2, 3]);
+
+        let mut one = list_from(&[1]);
+        one.rotate_left(1);
+        one.rotate_right(0);
+        check_links(&one);
+        assert_eq!(one.front(), Some(&1));
+
+        let mut empty: LinkedList<i32> = LinkedList::new();
+        empty.rotate_left(0);
+        empty.rotate_right(0);
+        check_links(&empty);
+    }
+
+    #[test]
+    #[should_panic(expected = "Cannot rotate by more than the length")]
+    fn test_rotate_out_of_range() {
+        let mut m: LinkedList<i32> = (0..3).collect();
+        m.rotate_right(4);
+    }
+
+    #[test]
+    fn test_pool_churn() {
+        let alloc = CountingAlloc::default();
+        let mut m = LinkedList::with_pool_in(alloc.clone());
+        m.reserve_nodes(16);
+        assert_eq!(m.pooled_nodes(), 16);
+        // All reserved nodes come in one allocation
+        assert_eq!(alloc.allocations(), 1);
+
+        for i in 0..1000 {
+            m.push_back(i);
+            m.push_front(i);
+            if i % 4 == 0 {
+                let mut cursor = m.cursor_front_mut();
+                cursor.insert_after(i);
+                cursor.remove_current();
+            }
+            assert_eq!(m.pop_front(), Some(i));
+            if i >= 8 {
+                m.pop_back();
+            }
+        }
+        assert_eq!(alloc.allocations(), 1);
+        check_links(&m);
+        assert_eq!(m.len() + m.pooled_nodes(), 16);
+
+        m.retain(|x| x % 2 == 0);
+        check_links(&m);
+        assert_eq!(m.len() + m.pooled_nodes(), 16);
+
+        m.shrink_to_fit();
+        assert_eq!(m.pooled_nodes(), 0);
+        m.clear();
+        assert_eq!(m.pooled_nodes(), 4);
+        drop(m);
+        assert_eq!(alloc.live(), 0);
+    }
+
+    #[test]
+    fn test_pool_nodes_move_between_lists() {
+        let alloc = CountingAlloc::default();
+        let mut m = LinkedList::with_pool_in(alloc.clone());
+        m.reserve_nodes(4);
+        m.extend(["a", "b", "c", "d"].map(String::from));
+        assert_eq!(m.pooled_nodes(), 0);
+
+        // Pooled nodes can be freed by a list without pool, and the other way around
+        let mut n = m.split_off(2);
+        n.pop_back();
+        m.append(&mut n);
+        assert_eq!(m.pop_back(), Some(String::from("c")));
+        assert_eq!(m.pooled_nodes(), 1);
+        check_links(&m);
+        assert_eq!(m.iter().cloned().collect::<Vec<_>>(), &["a", "b"]);
+
+        let mut p = LinkedList::new_in(alloc.clone());
+        p.reserve_nodes(2);
+        p.push_back(String::from("e"));
+        m.prepend(&mut p);
+        assert_eq!(p.pooled_nodes(), 1);
+        assert_eq!(m.pooled_nodes(), 1);
+        check_links(&m);
+        assert_eq!(m.iter().cloned().collect::<Vec<_>>(), &["e", "a", "b"]);
+        assert_eq!(alloc.live(), 2);
+
+        // Chunk reserved by m outlives it, until its last node is freed by another list
+        let rest = m.split_off(1);
+        drop(m);
+        assert_eq!(alloc.live(), 2);
+        drop(rest);
+        assert_eq!(alloc.live(), 1);
+        drop(p);
+        assert_eq!(alloc.live(), 0);
+    }
+
+    // Forwards to Global, keeps count of allocations across every clone
+    // Without pool every node is one allocation, reserved pool nodes share one per reserve
+    #[derive(Clone, Default)]
+    struct CountingAlloc(Rc<Counts>);
+
+    #[derive(Default)]
+    struct Counts {
+        // Alive in memory
+        live: Cell<usize>,
+        // Made so far
+        total: Cell<usize>,
+    }
+
+    impl CountingAlloc {
+        fn live(&self) -> usize {
+            self.0.live.get()
+        }
+
+        fn allocations(&self) -> usize {
+            self.0.total.get()
+        }
+    }
+
+    unsafe impl NodeAllocator for CountingAlloc {
+        fn allocate(&self, layout: Layout) -> Option<NonNull<u8>> {
+            self.0.live.set(self.0.live.get() + 1);
+            self.0.total.set(self.0.total.get() + 1);
+            Global.allocate(layout)
+        }
+
+        unsafe fn deallocate(&self, ptr: NonNull<u8>, layout: Layout) {
+            self.0.live.set(self.0.live.get() - 1);
+            Global.deallocate(ptr, layout)
+        }
+
+        // Clones share the counts, so freeing through any of them keeps them right
+        fn same_as(&self, other: &Self) -> bool {
+            Rc::ptr_eq(&self.0, &other.0)
+        }
+    }
+
+    #[test]
+    fn test_allocator() {
+        let alloc = CountingAlloc::default();
+        let mut m = LinkedList::new_in(alloc.clone());
+        m.extend(0..10);
+        assert_eq!(alloc.live(), 10);
+
+        // Lists made out of m share its allocator
+        let mut n = m.split_off(5);
+        assert_eq!(n.allocator().live(), 10);
+        let c = n.clone();
+        assert_eq!(alloc.live(), 15);
+        drop(c);
+        assert_eq!(alloc.live(), 10);
+
+        let drained = m.drain(1..3).collect::<Vec<_>>();
+        assert_eq!(drained, &[1, 2]);
+        let mut cursor = n.cursor_front_mut();
+        cursor.move_next();
+        let back = cursor.split_after();
+        assert_eq!(alloc.live(), 8);
+        drop(back);
+        assert_eq!(alloc.live(), 5);
+
+        m.append(&mut n);
+        check_links(&m);
+        assert_eq!(m.iter().copied().collect::<Vec<_>>(), &[0, 3, 4, 5, 6]);
+        m.sort_by(|a, b| b.cmp(a));
+        check_links(&m);
+        assert_eq!(alloc.live(), 5);
+
+        drop(m);
+        drop(n);
+        assert_eq!(alloc.live(), 0);
+    }
+
+    #[test]
+    fn test_allocator_pool() {
+        let alloc = CountingAlloc::default();
+        let mut m = LinkedList::with_pool_in(alloc.clone());
+        m.reserve_nodes(8);
+        assert_eq!(alloc.live(), 1);
+        m.extend(0..4);
+        m.pop_back();
+        assert_eq!(alloc.allocations(), 1);
+
+        // Chunk is still used by the nodes in the list
+        m.shrink_to_fit();
+        assert_eq!(m.pooled_nodes(), 0);
+        assert_eq!(alloc.live(), 1);
+
+        // Pool is empty, so node gets its own allocation
+        m.push_back(3);
+        assert_eq!(alloc.live(), 2);
+        m.reserve_nodes(2);
+        assert_eq!(alloc.live(), 3);
+
+        // Pooled nodes go back to the allocator when list is dropped
+        m.clear();
+        assert_eq!(m.pooled_nodes(), 6);
+        drop(m);
+        assert_eq!(alloc.live(), 0);
+    }
+
+    #[test]
+    fn test_borrowed_allocator() {
+        let alloc = CountingAlloc::default();
+        {
+            let mut m = LinkedList::new_in(&alloc);
+            let mut n = LinkedList::new_in(&alloc);
+            m.extend(["a", "b"]);
+            n.extend(["c", "d"]);
+            n.cursor_front_mut().splice_before(m.split_off(1));
+            m.append(&mut n);
+            check_links(&m);
+            assert_eq!(m.iter().copied().collect::<Vec<_>>(), &["a", "b", "c", "d"]);
+            assert_eq!(alloc.live(), 4);
+        }
+        assert_eq!(alloc.live(), 0);
+    }
+
+    #[test]
+    fn test_distinct_allocators() {
+        // Two arenas of the same type, nodes of one must never end up freed by the other
+        let arena1 = CountingAlloc::default();
+        let arena2 = CountingAlloc::default();
+        let mut m = LinkedList::new_in(&arena1);
+        let mut n = LinkedList::new_in(&arena2);
+        m.extend([1, 3]);
+        n.extend([2, 4]);
+
+        let panics = |f: &mut dyn FnMut()| {
+            std::panic::catch_unwind(std::panic::AssertUnwindSafe(f)).is_err()
+        };
+        assert!(panics(&mut || m.append(&mut n)));
+        assert!(panics(&mut || m.prepend(&mut n)));
+        assert!(panics(&mut || m.merge(&mut n)));
+        assert!(panics(&mut || m
+            .cursor_front_mut()
+            .splice_before(n.split_off(1))));
+        assert!(panics(&mut || m
+            .cursor_front_mut()
+            .splice_after(n.split_off(0))));
+
+        // Nothing moved, every node goes back to its own arena
+        check_links(&m);
+        assert_eq!(m.iter().copied().collect::<Vec<_>>(), &[1, 3]);
+        assert!(n.is_empty());
+        assert_eq!((arena1.live(), arena2.live()), (2, 0));
+        drop(m);
+        assert_eq!(arena1.live(), 0);
+    }
+
+    #[test]
+    #[should_panic(expected = "Lists use different allocators")]
+    fn test_distinct_allocators_append() {
+        let mut m = LinkedList::new_in(CountingAlloc::default());
+        let mut n = LinkedList::new_in(CountingAlloc::default());
+        n.push_back(1);
+        m.append(&mut n);
+    }
+
+    // Shared counters for Bomb, clone panics once clones_left runs out
+    struct Bombs {
+        drops: Cell<usize>,
+        clones_left: Cell<usize>,
+    }
+
+    impl Bombs {
+        fn new(clones_left: usize) -> Self {
+            Self {
+                drops: Cell::new(0),
+                clones_left: Cell::new(clones_left),
+            }
+        }
+
+        fn make(&self, explode_on_drop: bool) -> Bomb<'_> {
+            Bomb {
+                bombs: self,
+                explode_on_drop,
+            }
+        }
+    }
+
+    struct Bomb<'a> {
+        bombs: &'a Bombs,
+        explode_on_drop: bool,
+    }
+
+    impl<'a> Clone for Bomb<'a> {
+        fn clone(&self) -> Self {
+            let left = self.bombs.clones_left.get();
+            assert!(left > 0, "clone exploded");
+            self.bombs.clones_left.set(left - 1);
+            self.bombs.make(false)
+        }
+    }
+
+    impl<'a> Drop for Bomb<'a> {
+        fn drop(&mut self) {
+            self.bombs.drops.set(self.bombs.drops.get() + 1);
+            if self.explode_on_drop {
+                panic!("drop exploded");
+            }
+        }
+    }
+
+    #[test]
+    fn test_drop_panic() {
+        let bombs = Bombs::new(0);
+        let alloc = CountingAlloc::default();
+        let mut m = LinkedList::with_pool_in(alloc.clone());
+        m.reserve_nodes(8);
+        for i in 0..6 {
+            m.push_back(bombs.make(i == 2));
+        }
+
+        let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| drop(m)));
+        assert!(result.is_err());
+
+        // Every value dropped exactly once, every node and pooled node freed
+        assert_eq!(bombs.drops.get(), 6);
+        assert_eq!(alloc.live(), 0);
+    }
+
+    #[test]
+    fn test_into_iter_drop_panic() {
+        let bombs = Bombs::new(0);
+        let alloc = CountingAlloc::default();
+        let mut m = LinkedList::new_in(alloc.clone());
+        for i in 0..6 {
+            m.push_back(bombs.make(i == 4));
+        }
+
+        let mut iter = m.into_iter();
+        drop(iter.next());
+        drop(iter.next_back());
+        let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| drop(iter)));
+        assert!(result.is_err());
+        assert_eq!(bombs.drops.get(), 6);
+        assert_eq!(alloc.live(), 0);
+    }
+
+    #[test]
+    fn test_clone_panic() {
+        let bombs = Bombs::new(3);
+        let alloc = CountingAlloc::default();
+        let mut m = LinkedList::new_in(alloc.clone());
+        for _ in 0..5 {
+            m.push_back(bombs.make(false));
+        }
+
+        let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| m.clone()));
+        assert!(result.is_err());
+
+        // Partial clone is gone, source list is untouched
+        assert_eq!(bombs.drops.get(), 3);
+        assert_eq!(alloc.live(), 5);
+        assert_eq!(m.len(), 5);
+
+        drop(m);
+        assert_eq!(bombs.drops.get(), 8);
+        assert_eq!(alloc.live(), 0);
+    }
+
+    #[test]
+    fn test_extend_panic() {
+        let alloc = CountingAlloc::default();
+        let mut m = LinkedList::new_in(alloc.clone());
+        m.push_back(-1);
+
+        let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            m.extend((0..5).inspect(|&i| assert!(i < 3)))
+        }));
+        assert!(result.is_err());
+        check_links(&m);
+        assert_eq!(m.iter().copied().collect::<Vec<_>>(), &[-1, 0, 1, 2]);
+        assert_eq!(alloc.live(), 4);
+
+        drop(m);
+        assert_eq!(alloc.live(), 0);
+    }
+
+    fn check_links<T: Eq + Debug, A: NodeAllocator>(list: &LinkedList<T, A>) {
         let from_front: Vec<_> = list.iter().collect();
         let from_back: Vec<_> = list.iter().rev().collect();
         let re_reved: Vec<_> = from_back.into_iter().rev().collect();
 
         assert_eq!(from_front, re_reved);
+
+        // Walk raw links to make sure len, head and tail agree with the nodes
+        unsafe {
+            let mut len = 0;
+            let mut last = None;
+            let mut cur = list.head;
+            while let Some(node) = cur {
+                assert_eq!((*node.as_ptr()).prev, last);
+                last = Some(node);
+                cur = (*node.as_ptr()).next;
+                len += 1;
+            }
+            assert_eq!(list.tail, last);
+            assert_eq!(list.len, len);
+        }
     }
 }
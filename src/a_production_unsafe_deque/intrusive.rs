@@ -0,0 +1,889 @@
+use super::raw::{self, Ends, RawList};
+use std::cell::Cell;
+use std::fmt::{self, Debug, Formatter};
+use std::marker::{PhantomData, PhantomPinned};
+use std::mem;
+use std::pin::Pin;
+use std::ptr::NonNull;
+
+// Intrusive version of first::LinkedList
+// Elements carry their own links, so the list never allocates and elements never move
+//
+//     list.front -> [Task | links] <-> [Task | links] <-> [Task | links] <- list.back
+//
+// Elements are pinned, their address has to stay the same for as long as they are linked
+// Links are Cells, so list can relink neighbours while user holds shared references to them
+
+// Embedded by user in their own type, one per list the type can be linked into
+pub struct Links<T> {
+    next: Cell<Option<NonNull<T>>>,
+    prev: Cell<Option<NonNull<T>>>,
+    linked: Cell<bool>,
+    // Makes the element !Unpin, so safe code can't move it out of its Pin
+    _pin: PhantomPinned,
+}
+
+impl<T> Links<T> {
+    pub const fn new() -> Self {
+        Self {
+            next: Cell::new(None),
+            prev: Cell::new(None),
+            linked: Cell::new(false),
+            _pin: PhantomPinned,
+        }
+    }
+
+    pub fn is_linked(&self) -> bool {
+        self.linked.get()
+    }
+}
+
+impl<T> Default for Links<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> Debug for Links<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Links")
+            .field("linked", &self.is_linked())
+            .finish()
+    }
+}
+
+impl<T> Drop for Links<T> {
+    fn drop(&mut self) {
+        // Some list still points to this element, going on would leave it with a dangling node
+        // While linked, the list holds the only pointer to the element (its Box or its &mut)
+        // and hands out nothing but Pin<&Elem>, so nobody can drop it or Pin::set it in place.
+        // What's left is leaking a list (mem::forget) whose elements are borrowed, which ends
+        // the borrow but never uses the list again, so unwinding out of here is fine
+        // (panicking while already unwinding would abort anyway, do it right away)
+        if self.is_linked() {
+            if std::thread::panicking() {
+                std::process::abort();
+            }
+            panic!("Element dropped while still linked into a list");
+        }
+    }
+}
+
+// Links only travel together with their element, which the list owns or borrows mutably
+unsafe impl<T: Send> Send for Links<T> {}
+
+/// Pointer a list can hold its elements by, turned into a raw pointer while linked
+///
+/// # Safety
+///
+/// Pointer returned by `into_raw` must point to a pinned value which stays valid
+/// until `from_raw` takes it back
+pub unsafe trait PinPointer {
+    type Target;
+
+    fn into_raw(self) -> NonNull<Self::Target>;
+
+    /// # Safety
+    ///
+    /// `ptr` must come from `into_raw` of the same pointer type, and be taken back only once
+    unsafe fn from_raw(ptr: NonNull<Self::Target>) -> Self;
+}
+
+// List owns the element, dropping the list drops it
+unsafe impl<T> PinPointer for Pin<Box<T>> {
+    type Target = T;
+
+    fn into_raw(self) -> NonNull<T> {
+        // Pointer goes back into a Pin<Box<T>> in from_raw, so pinning is never broken
+        unsafe { NonNull::new_unchecked(Box::into_raw(Pin::into_inner_unchecked(self))) }
+    }
+
+    unsafe fn from_raw(ptr: NonNull<T>) -> Self {
+        Box::into_pin(Box::from_raw(ptr.as_ptr()))
+    }
+}
+
+// List borrows the element, e.g. one pinned on the stack or inside a slab
+unsafe impl<T> PinPointer for Pin<&mut T> {
+    type Target = T;
+
+    fn into_raw(self) -> NonNull<T> {
+        NonNull::from(unsafe { self.get_unchecked_mut() })
+    }
+
+    unsafe fn from_raw(ptr: NonNull<T>) -> Self {
+        Pin::new_unchecked(&mut *ptr.as_ptr())
+    }
+}
+
+/// Tells a list how to reach the Links field of its elements, one adapter per field
+///
+/// # Safety
+///
+/// `links` must always return the same field of `value`,
+/// and that field must not be used by any other adapter at the same time
+pub unsafe trait Adapter {
+    type Pointer: PinPointer;
+
+    fn links(
+        value: &<Self::Pointer as PinPointer>::Target,
+    ) -> &Links<<Self::Pointer as PinPointer>::Target>;
+}
+
+type Elem<A> = <<A as Adapter>::Pointer as PinPointer>::Target;
+
+/// Linked elements are only handed out as `Pin<&T>`, so safe code can't overwrite
+/// their links with `Pin::set` while the list still points to them:
+///
+/// ```compile_fail
+/// use rust_too_many_linked_lists::a_production_unsafe_deque::intrusive::{Adapter, LinkedList, Links};
+/// use std::pin::Pin;
+///
+/// struct Task {
+///     links: Links<Task>,
+/// }
+///
+/// struct Tasks;
+///
+/// unsafe impl Adapter for Tasks {
+///     type Pointer = Pin<Box<Task>>;
+///
+///     fn links(task: &Task) -> &Links<Task> {
+///         &task.links
+///     }
+/// }
+///
+/// let mut list = LinkedList::<Tasks>::new();
+/// list.push_back(Box::pin(Task { links: Links::new() }));
+/// list.cursor_front_mut().current().unwrap().set(Task { links: Links::new() });
+/// ```
+pub struct LinkedList<A: Adapter> {
+    head: Option<NonNull<Elem<A>>>,
+    tail: Option<NonNull<Elem<A>>>,
+    len: usize,
+    // List holds its elements through A::Pointer, which may borrow them
+    _phantom: PhantomData<A::Pointer>,
+}
+
+impl<A: Adapter> LinkedList<A> {
+    pub fn new() -> Self {
+        Self {
+            head: None,
+            tail: None,
+            len: 0,
+            _phantom: PhantomData,
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn clear(&mut self) {
+        while self.pop_front().is_some() {}
+    }
+
+    pub fn push_front(&mut self, val: A::Pointer) {
+        let node = val.into_raw();
+        unsafe { self.link_elem(node, None, self.head) };
+    }
+
+    pub fn push_back(&mut self, val: A::Pointer) {
+        let node = val.into_raw();
+        unsafe { self.link_elem(node, self.tail, None) };
+    }
+
+    pub fn pop_front(&mut self) -> Option<A::Pointer> {
+        self.head.map(|node| unsafe { self.unlink(node) })
+    }
+
+    pub fn pop_back(&mut self) -> Option<A::Pointer> {
+        self.tail.map(|node| unsafe { self.unlink(node) })
+    }
+
+    // No front_mut or back_mut: Pin<&mut Elem> would let safe code Pin::set a linked element
+    // and overwrite its Links, elements are changed through their own Cells instead
+    pub fn front(&self) -> Option<Pin<&Elem<A>>> {
+        self.head
+            .map(|node| unsafe { Pin::new_unchecked(&*node.as_ptr()) })
+    }
+
+    pub fn back(&self) -> Option<Pin<&Elem<A>>> {
+        self.tail
+            .map(|node| unsafe { Pin::new_unchecked(&*node.as_ptr()) })
+    }
+
+    /// O(1) removal of an element we only have a reference to, e.g. a task being cancelled.
+    /// Pointer to it can be made with `NonNull::from(&*elem)`
+    ///
+    /// # Safety
+    ///
+    /// `node` must be linked into this list (not just any list) through adapter `A`
+    pub unsafe fn remove(&mut self, node: NonNull<Elem<A>>) -> A::Pointer {
+        self.unlink(node)
+    }
+
+    pub fn append(&mut self, other: &mut Self) {
+        unsafe { self.splice_between(other, self.tail, None) };
+    }
+
+    pub fn prepend(&mut self, other: &mut Self) {
+        unsafe { self.splice_between(other, None, self.head) };
+    }
+
+    pub fn iter(&self) -> Iter<'_, A> {
+        Iter {
+            head: self.head,
+            tail: self.tail,
+            len: self.len,
+            _phantom: PhantomData,
+        }
+    }
+
+    // Cursor starts at ghost node, like first::LinkedList::cursor_mut
+    pub fn cursor_mut(&mut self) -> CursoMut<'_, A> {
+        CursoMut {
+            cur: None,
+            index: None,
+            list: self,
+        }
+    }
+
+    pub fn cursor_front_mut(&mut self) -> CursoMut<'_, A> {
+        CursoMut {
+            cur: self.head,
+            index: self.head.map(|_| 0),
+            list: self,
+        }
+    }
+
+    pub fn cursor_back_mut(&mut self) -> CursoMut<'_, A> {
+        CursoMut {
+            cur: self.tail,
+            index: self.tail.map(|_| self.len - 1),
+            list: self,
+        }
+    }
+
+    // SAFETY: node must point to a live element, every node linked into a list does
+    unsafe fn links<'b>(node: NonNull<Elem<A>>) -> &'b Links<Elem<A>> {
+        A::links(&*node.as_ptr())
+    }
+
+    // Link element of a pointer we got, and mark it as linked
+    // SAFETY: node must come from A::Pointer::into_raw,
+    // prev and next must be neighbours in this list (None is list end)
+    unsafe fn link_elem(
+        &mut self,
+        node: NonNull<Elem<A>>,
+        prev: Option<NonNull<Elem<A>>>,
+        next: Option<NonNull<Elem<A>>>,
+    ) {
+        let links = Self::links(node);
+        // Relinking would corrupt the other list, leak the pointer instead
+        assert!(!links.is_linked(), "Element is already linked into a list");
+        links.linked.set(true);
+        self.link_between(node, prev, next);
+    }
+
+    // Detach node and give back the pointer it was pushed with
+    // SAFETY: node must be linked into this list
+    unsafe fn unlink(&mut self, node: NonNull<Elem<A>>) -> A::Pointer {
+        self.unlink_node(node);
+        Self::links(node).linked.set(false);
+        A::Pointer::from_raw(node)
+    }
+}
+
+// Links live in the element, reached through adapter A
+impl<A: Adapter> RawList for LinkedList<A> {
+    type Node = Elem<A>;
+
+    fn ends(&mut self) -> Ends<'_, Elem<A>> {
+        (&mut self.head, &mut self.tail, &mut self.len)
+    }
+
+    unsafe fn next(node: NonNull<Elem<A>>) -> Option<NonNull<Elem<A>>> {
+        Self::links(node).next.get()
+    }
+
+    unsafe fn prev(node: NonNull<Elem<A>>) -> Option<NonNull<Elem<A>>> {
+        Self::links(node).prev.get()
+    }
+
+    unsafe fn set_next(node: NonNull<Elem<A>>, next: Option<NonNull<Elem<A>>>) {
+        Self::links(node).next.set(next);
+    }
+
+    unsafe fn set_prev(node: NonNull<Elem<A>>, prev: Option<NonNull<Elem<A>>>) {
+        Self::links(node).prev.set(prev);
+    }
+}
+
+impl<A: Adapter> Drop for LinkedList<A> {
+    fn drop(&mut self) {
+        // Same as first::LinkedList, if an element destructor panics,
+        // guard still unlinks the rest, so borrowed elements are never left linked
+        struct DropGuard<'a, A: Adapter>(&'a mut LinkedList<A>);
+
+        impl<'a, A: Adapter> Drop for DropGuard<'a, A> {
+            fn drop(&mut self) {
+                self.0.clear();
+            }
+        }
+
+        let guard = DropGuard(self);
+        while guard.0.pop_front().is_some() {}
+    }
+}
+
+impl<A: Adapter> Default for LinkedList<A> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<A: Adapter> Debug for LinkedList<A>
+where
+    Elem<A>: Debug,
+{
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self).finish()
+    }
+}
+
+// List behaves like a collection of A::Pointer
+// Never Sync: elements embed Links, whose Cells are not Sync, so &list can't cross threads
+// (same goes for Iter and CursoMut, which only borrow the list)
+unsafe impl<A: Adapter> Send for LinkedList<A> where A::Pointer: Send {}
+
+pub struct Iter<'a, A: Adapter> {
+    head: Option<NonNull<Elem<A>>>,
+    tail: Option<NonNull<Elem<A>>>,
+    len: usize,
+    _phantom: PhantomData<&'a Elem<A>>,
+}
+
+impl<'a, A: Adapter> Iterator for Iter<'a, A> {
+    type Item = Pin<&'a Elem<A>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.len == 0 {
+            return None;
+        }
+
+        self.head.map(|node| unsafe {
+            self.len -= 1;
+            self.head = LinkedList::<A>::links(node).next.get();
+            Pin::new_unchecked(&*node.as_ptr())
+        })
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len, Some(self.len))
+    }
+}
+
+impl<'a, A: Adapter> DoubleEndedIterator for Iter<'a, A> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        if self.len == 0 {
+            return None;
+        }
+
+        self.tail.map(|node| unsafe {
+            self.len -= 1;
+            self.tail = LinkedList::<A>::links(node).prev.get();
+            Pin::new_unchecked(&*node.as_ptr())
+        })
+    }
+}
+
+impl<'a, A: Adapter> ExactSizeIterator for Iter<'a, A> {
+    fn len(&self) -> usize {
+        self.len
+    }
+}
+
+impl<'a, A: Adapter> IntoIterator for &'a LinkedList<A> {
+    type Item = Pin<&'a Elem<A>>;
+    type IntoIter = Iter<'a, A>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+// Same ghost node model as first::CursoMut:
+// cur None with index None is the ghost node between back and front
+pub struct CursoMut<'a, A: Adapter> {
+    cur: Option<NonNull<Elem<A>>>,
+    index: Option<usize>,
+    list: &'a mut LinkedList<A>,
+}
+
+impl<'a, A: Adapter> CursoMut<'a, A> {
+    pub fn index(&self) -> Option<usize> {
+        self.index
+    }
+
+    pub fn current(&self) -> Option<Pin<&Elem<A>>> {
+        self.cur
+            .map(|node| unsafe { Pin::new_unchecked(&*node.as_ptr()) })
+    }
+
+    pub fn peek_next(&self) -> Option<Pin<&Elem<A>>> {
+        let next = unsafe { raw::next_of::<LinkedList<A>>(self.cur, self.list.head) };
+        next.map(|node| unsafe { Pin::new_unchecked(&*node.as_ptr()) })
+    }
+
+    pub fn peek_prev(&self) -> Option<Pin<&Elem<A>>> {
+        let prev = unsafe { raw::prev_of::<LinkedList<A>>(self.cur, self.list.tail) };
+        prev.map(|node| unsafe { Pin::new_unchecked(&*node.as_ptr()) })
+    }
+
+    pub fn move_next(&mut self) {
+        unsafe { raw::move_next::<LinkedList<A>>(&mut self.cur, &mut self.index, self.list.head) };
+    }
+
+    pub fn move_prev(&mut self) {
+        let (tail, len) = (self.list.tail, self.list.len);
+        unsafe { raw::move_prev::<LinkedList<A>>(&mut self.cur, &mut self.index, tail, len) };
+    }
+
+    pub fn insert_before(&mut self, val: A::Pointer) {
+        let node = val.into_raw();
+        // Before ghost node is the back of the list
+        unsafe {
+            let prev = raw::prev_of::<LinkedList<A>>(self.cur, self.list.tail);
+            self.list.link_elem(node, prev, self.cur);
+        }
+        if let Some(index) = self.index.as_mut() {
+            *index += 1;
+        }
+    }
+
+    pub fn insert_after(&mut self, val: A::Pointer) {
+        let node = val.into_raw();
+        // After ghost node is the front of the list
+        unsafe {
+            let next = raw::next_of::<LinkedList<A>>(self.cur, self.list.head);
+            self.list.link_elem(node, self.cur, next);
+        }
+    }
+
+    pub fn remove_current(&mut self) -> Option<A::Pointer> {
+        // Cursor at ghost node has nothing to remove
+        let cur = self.cur?;
+
+        unsafe {
+            // Next element takes over current index, if removed one is the back, we move to ghost node
+            self.cur = LinkedList::<A>::links(cur).next.get();
+            if self.cur.is_none() {
+                self.index = None;
+            }
+            Some(self.list.unlink(cur))
+        }
+    }
+
+    pub fn splice_before(&mut self, mut input: LinkedList<A>) {
+        let len = input.len;
+        unsafe {
+            let prev = raw::prev_of::<LinkedList<A>>(self.cur, self.list.tail);
+            self.list.splice_between(&mut input, prev, self.cur);
+        }
+        if let Some(index) = self.index.as_mut() {
+            *index += len;
+        }
+    }
+
+    pub fn splice_after(&mut self, mut input: LinkedList<A>) {
+        // Elements go after cursor, so its index stays the same
+        unsafe {
+            let next = raw::next_of::<LinkedList<A>>(self.cur, self.list.head);
+            self.list.splice_between(&mut input, self.cur, next);
+        }
+    }
+
+    // Everything before current element, whole list at ghost node
+    pub fn split_before(&mut self) -> LinkedList<A> {
+        let mut ret = LinkedList::new();
+        match (self.index.as_mut(), self.cur) {
+            (Some(index), Some(cur)) => unsafe {
+                self.list.split_before_node(cur, *index, &mut ret);
+                *index = 0;
+            },
+            _ => mem::swap(&mut ret, self.list),
+        }
+        ret
+    }
+
+    // Everything after current element, whole list at ghost node
+    pub fn split_after(&mut self) -> LinkedList<A> {
+        let mut ret = LinkedList::new();
+        match (self.index, self.cur) {
+            (Some(index), Some(cur)) => unsafe { self.list.split_after_node(cur, index, &mut ret) },
+            _ => mem::swap(&mut ret, self.list),
+        }
+        ret
+    }
+}
+
+// CursoMut behaves like &mut LinkedList<A>
+unsafe impl<'a, A: Adapter> Send for CursoMut<'a, A> where A::Pointer: Send {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::pin::pin;
+
+    #[derive(Debug)]
+    struct Task {
+        id: u32,
+        links: Links<Task>,
+        // Second field, so a task could sit in another list at the same time
+        ready: Links<Task>,
+    }
+
+    impl Task {
+        fn new(id: u32) -> Self {
+            Self {
+                id,
+                links: Links::new(),
+                ready: Links::new(),
+            }
+        }
+    }
+
+    struct Owned;
+
+    unsafe impl Adapter for Owned {
+        type Pointer = Pin<Box<Task>>;
+
+        fn links(task: &Task) -> &Links<Task> {
+            &task.links
+        }
+    }
+
+    struct Borrowed<'a>(PhantomData<&'a mut Task>);
+
+    unsafe impl<'a> Adapter for Borrowed<'a> {
+        type Pointer = Pin<&'a mut Task>;
+
+        fn links(task: &Task) -> &Links<Task> {
+            &task.ready
+        }
+    }
+
+    fn boxed(ids: impl IntoIterator<Item = u32>) -> LinkedList<Owned> {
+        let mut list = LinkedList::new();
+        for id in ids {
+            list.push_back(Box::pin(Task::new(id)));
+        }
+        list
+    }
+
+    fn ids<A: Adapter>(list: &LinkedList<A>) -> Vec<u32>
+    where
+        A::Pointer: PinPointer<Target = Task>,
+    {
+        list.iter().map(|task| task.id).collect()
+    }
+
+    fn check_links<A: Adapter>(list: &LinkedList<A>) {
+        unsafe {
+            let mut len = 0;
+            let mut last = None;
+            let mut cur = list.head;
+            while let Some(node) = cur {
+                let links = LinkedList::<A>::links(node);
+                assert!(links.is_linked());
+                assert_eq!(links.prev.get(), last);
+                last = Some(node);
+                cur = links.next.get();
+                len += 1;
+            }
+            assert_eq!(list.tail, last);
+            assert_eq!(list.len, len);
+        }
+        assert_eq!(list.iter().rev().count(), list.len());
+    }
+
+    #[test]
+    fn test_owned() {
+        let mut list = LinkedList::<Owned>::new();
+        assert!(list.is_empty());
+        assert!(list.pop_front().is_none());
+
+        list.push_back(Box::pin(Task::new(2)));
+        list.push_front(Box::pin(Task::new(1)));
+        list.push_back(Box::pin(Task::new(3)));
+        check_links(&list);
+        assert_eq!(ids(&list), &[1, 2, 3]);
+        assert_eq!(list.front().map(|task| task.id), Some(1));
+        assert_eq!(list.back().map(|task| task.id), Some(3));
+
+        // Popped element is unlinked and can go into another list
+        let task = list.pop_back().unwrap();
+        assert_eq!(task.id, 3);
+        assert!(!task.links.is_linked());
+        let mut other = LinkedList::<Owned>::new();
+        other.push_back(task);
+        check_links(&other);
+
+        let task = list.pop_front().unwrap();
+        assert_eq!(task.id, 1);
+        check_links(&list);
+        assert_eq!(ids(&list), &[2]);
+        assert!(format!("{:?}", list).starts_with("[Task { id: 2, links: Links { linked: true }"));
+    }
+
+    #[test]
+    fn test_borrowed() {
+        let mut a = pin!(Task::new(1));
+        let mut b = pin!(Task::new(2));
+        let mut c = pin!(Task::new(3));
+
+        {
+            let mut list = LinkedList::<Borrowed<'_>>::new();
+            list.push_back(a.as_mut());
+            list.push_back(b.as_mut());
+            list.push_front(c.as_mut());
+            check_links(&list);
+            assert_eq!(ids(&list), &[3, 1, 2]);
+
+            let b_ptr = NonNull::from(&*list.back().unwrap());
+            let b_back = unsafe { list.remove(b_ptr) };
+            assert_eq!(b_back.id, 2);
+            check_links(&list);
+            assert_eq!(ids(&list), &[3, 1]);
+        }
+
+        // Dropping the list unlinks whatever it still borrowed
+        assert!(!a.ready.is_linked());
+        assert!(!b.ready.is_linked());
+        assert!(!c.ready.is_linked());
+
+        let mut list = LinkedList::<Borrowed<'_>>::new();
+        list.push_back(b.as_mut());
+        list.push_back(a.as_mut());
+        assert_eq!(ids(&list), &[2, 1]);
+    }
+
+    #[test]
+    #[should_panic(expected = "Element dropped while still linked into a list")]
+    fn test_drop_linked() {
+        // Leaking the list ends its borrow, while the element stays linked
+        let mut task = pin!(Task::new(1));
+        let mut list = LinkedList::<Borrowed<'_>>::new();
+        list.push_back(task.as_mut());
+        mem::forget(list);
+    }
+
+    #[test]
+    fn test_mutate_linked() {
+        struct Counter {
+            links: Links<Counter>,
+            count: Cell<u32>,
+        }
+
+        struct Counters;
+
+        unsafe impl Adapter for Counters {
+            type Pointer = Pin<Box<Counter>>;
+
+            fn links(counter: &Counter) -> &Links<Counter> {
+                &counter.links
+            }
+        }
+
+        let mut list = LinkedList::<Counters>::new();
+        for count in 0..3 {
+            list.push_back(Box::pin(Counter {
+                links: Links::new(),
+                count: Cell::new(count),
+            }));
+        }
+
+        // Shared pins are enough to change values, links stay untouched
+        list.front().unwrap().count.set(10);
+        let mut cursor = list.cursor_front_mut();
+        cursor.move_next();
+        cursor.current().unwrap().count.set(11);
+        cursor.peek_next().unwrap().count.set(12);
+        check_links(&list);
+        let counts: Vec<_> = list.iter().map(|counter| counter.count.get()).collect();
+        assert_eq!(counts, &[10, 11, 12]);
+    }
+
+    #[test]
+    fn test_cursor() {
+        let mut list = boxed([1, 2, 3]);
+        let mut cursor = list.cursor_mut();
+        assert_eq!(cursor.index(), None);
+        assert!(cursor.current().is_none());
+        assert_eq!(cursor.peek_next().map(|t| t.id), Some(1));
+        assert_eq!(cursor.peek_prev().map(|t| t.id), Some(3));
+
+        cursor.move_next();
+        cursor.move_next();
+        assert_eq!(cursor.index(), Some(1));
+        assert_eq!(cursor.current().map(|t| t.id), Some(2));
+
+        cursor.insert_before(Box::pin(Task::new(10)));
+        cursor.insert_after(Box::pin(Task::new(20)));
+        assert_eq!(cursor.index(), Some(2));
+        assert_eq!(cursor.peek_prev().map(|t| t.id), Some(10));
+        assert_eq!(cursor.peek_next().map(|t| t.id), Some(20));
+
+        let removed = cursor.remove_current().unwrap();
+        assert_eq!(removed.id, 2);
+        assert!(!removed.links.is_linked());
+        assert_eq!(cursor.current().map(|t| t.id), Some(20));
+        assert_eq!(cursor.index(), Some(2));
+
+        cursor.move_next();
+        cursor.move_next();
+        assert_eq!(cursor.index(), None);
+        // Ghost node inserts at both ends
+        cursor.insert_before(Box::pin(Task::new(30)));
+        cursor.insert_after(Box::pin(Task::new(0)));
+        assert!(cursor.remove_current().is_none());
+
+        cursor.move_prev();
+        assert_eq!(cursor.index(), Some(5));
+        let removed = cursor.remove_current().unwrap();
+        assert_eq!(removed.id, 30);
+        assert_eq!(cursor.index(), None);
+
+        check_links(&list);
+        assert_eq!(ids(&list), &[0, 1, 10, 20, 3]);
+
+        let mut cursor = list.cursor_back_mut();
+        assert_eq!(cursor.index(), Some(4));
+        cursor.move_prev();
+        assert_eq!(cursor.current().map(|t| t.id), Some(20));
+        let mut cursor = list.cursor_front_mut();
+        cursor.move_prev();
+        assert_eq!(cursor.index(), None);
+    }
+
+    #[test]
+    fn test_cursor_splice() {
+        let mut list = boxed([1, 2, 3]);
+        let mut cursor = list.cursor_front_mut();
+        cursor.move_next();
+        cursor.splice_before(boxed([10, 11]));
+        assert_eq!(cursor.index(), Some(3));
+        cursor.splice_after(boxed([20]));
+        cursor.splice_after(LinkedList::new());
+        assert_eq!(cursor.current().map(|t| t.id), Some(2));
+
+        // Ghost node splices at both ends
+        cursor.move_prev();
+        cursor.move_prev();
+        cursor.move_prev();
+        cursor.move_prev();
+        assert_eq!(cursor.index(), None);
+        cursor.splice_before(boxed([30]));
+        cursor.splice_after(boxed([0]));
+        check_links(&list);
+        assert_eq!(ids(&list), &[0, 1, 10, 11, 2, 20, 3, 30]);
+
+        let mut other = boxed([40, 41]);
+        list.append(&mut other);
+        assert!(other.is_empty());
+        list.prepend(&mut boxed([99]));
+        list.append(&mut LinkedList::new());
+        check_links(&list);
+        check_links(&other);
+        assert_eq!(list.len(), 11);
+    }
+
+    #[test]
+    fn test_cursor_split() {
+        let mut list = boxed(0..6);
+        let mut cursor = list.cursor_front_mut();
+        cursor.move_next();
+        cursor.move_next();
+        let front = cursor.split_before();
+        assert_eq!(cursor.index(), Some(0));
+        cursor.move_next();
+        let back = cursor.split_after();
+        assert_eq!(cursor.index(), Some(1));
+        check_links(&front);
+        check_links(&back);
+        check_links(&list);
+        assert_eq!(ids(&front), &[0, 1]);
+        assert_eq!(ids(&list), &[2, 3]);
+        assert_eq!(ids(&back), &[4, 5]);
+
+        // Nothing before front, nothing after back
+        let mut cursor = list.cursor_front_mut();
+        assert!(cursor.split_before().is_empty());
+        let mut cursor = list.cursor_back_mut();
+        assert!(cursor.split_after().is_empty());
+        check_links(&list);
+        assert_eq!(ids(&list), &[2, 3]);
+
+        // Ghost node splits off everything
+        let mut cursor = list.cursor_mut();
+        let all = cursor.split_after();
+        assert!(cursor.split_before().is_empty());
+        check_links(&all);
+        check_links(&list);
+        assert_eq!(ids(&all), &[2, 3]);
+        assert!(list.is_empty());
+    }
+
+    #[test]
+    fn test_drop_panic() {
+        struct Loud {
+            links: Links<Loud>,
+            drops: std::rc::Rc<Cell<usize>>,
+            explode: bool,
+        }
+
+        impl Drop for Loud {
+            fn drop(&mut self) {
+                self.drops.set(self.drops.get() + 1);
+                assert!(!self.explode, "drop exploded");
+            }
+        }
+
+        struct LoudAdapter;
+
+        unsafe impl Adapter for LoudAdapter {
+            type Pointer = Pin<Box<Loud>>;
+
+            fn links(loud: &Loud) -> &Links<Loud> {
+                &loud.links
+            }
+        }
+
+        let drops = std::rc::Rc::new(Cell::new(0));
+        let mut list = LinkedList::<LoudAdapter>::new();
+        for i in 0..4 {
+            list.push_back(Box::pin(Loud {
+                links: Links::new(),
+                drops: drops.clone(),
+                explode: i == 1,
+            }));
+        }
+
+        let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| drop(list)));
+        assert!(result.is_err());
+        assert_eq!(drops.get(), 4);
+    }
+
+    #[test]
+    fn test_send() {
+        fn is_send<T: Send>() {}
+        is_send::<LinkedList<Owned>>();
+        is_send::<LinkedList<Borrowed<'static>>>();
+        is_send::<CursoMut<'static, Owned>>();
+    }
+}
@@ -1 +1,3 @@
-mod first;
\ No newline at end of file
+pub mod first;
+pub mod intrusive;
+mod raw;
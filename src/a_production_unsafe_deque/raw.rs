@@ -0,0 +1,250 @@
+use std::mem;
+use std::ptr::NonNull;
+
+// Relinking code shared by first::LinkedList and intrusive::LinkedList
+// Both are the same doubly linked list underneath
+//
+//     list.front -> A <-> B <-> C <- list.back
+//
+// and only differ in where a node keeps its next and prev pointers:
+// in first's Node next to the value, or in Links the user embeds in the element
+//
+// Nothing here allocates or frees, lists keep track of who owns the nodes
+
+// head, tail and len of a list
+pub(super) type Ends<'a, N> = (
+    &'a mut Option<NonNull<N>>,
+    &'a mut Option<NonNull<N>>,
+    &'a mut usize,
+);
+
+pub(super) trait RawList {
+    type Node;
+
+    fn ends(&mut self) -> Ends<'_, Self::Node>;
+
+    // SAFETY: node must point to a live node
+    unsafe fn next(node: NonNull<Self::Node>) -> Option<NonNull<Self::Node>>;
+    unsafe fn prev(node: NonNull<Self::Node>) -> Option<NonNull<Self::Node>>;
+    unsafe fn set_next(node: NonNull<Self::Node>, next: Option<NonNull<Self::Node>>);
+    unsafe fn set_prev(node: NonNull<Self::Node>, prev: Option<NonNull<Self::Node>>);
+
+    // Put node between prev and next, which must be neighbours in this list (None is list end)
+    // SAFETY: node must not be linked, prev and next must be linked into this list
+    unsafe fn link_between(
+        &mut self,
+        node: NonNull<Self::Node>,
+        prev: Option<NonNull<Self::Node>>,
+        next: Option<NonNull<Self::Node>>,
+    ) {
+        let (head, tail, len) = self.ends();
+        Self::set_prev(node, prev);
+        Self::set_next(node, next);
+
+        match prev {
+            Some(prev) => Self::set_next(prev, Some(node)),
+            None => *head = Some(node),
+        }
+        match next {
+            Some(next) => Self::set_prev(next, Some(node)),
+            None => *tail = Some(node),
+        }
+
+        *len += 1;
+    }
+
+    // Detach node from the list without freeing it, caller takes ownership of the node
+    // SAFETY: node must be linked into this list
+    unsafe fn unlink_node(&mut self, node: NonNull<Self::Node>) {
+        let (head, tail, len) = self.ends();
+        let prev = Self::prev(node);
+        let next = Self::next(node);
+        Self::set_prev(node, None);
+        Self::set_next(node, None);
+
+        match prev {
+            Some(prev) => Self::set_next(prev, next),
+            None => *head = next,
+        }
+        match next {
+            Some(next) => Self::set_prev(next, prev),
+            None => *tail = prev,
+        }
+
+        *len -= 1;
+    }
+
+    // Move every node of other between prev and next, which must be neighbours in this list
+    // SAFETY: prev and next must be linked into this list
+    unsafe fn splice_between(
+        &mut self,
+        other: &mut Self,
+        prev: Option<NonNull<Self::Node>>,
+        next: Option<NonNull<Self::Node>>,
+    ) {
+        // We have this:
+        //
+        // other.front -> 1 <-> 2 <- other.back
+        //
+        // list.front -> A <-> B <-> C <- list.back
+        //                     ^     ^
+        //                   prev   next
+        //
+        //
+        // Becoming this:
+        //
+        // list.front -> A <-> B <-> 1 <-> 2 <-> C <- list.back
+        //
+        let (other_head, other_tail, other_len) = other.ends();
+        let (first, last) = match (other_head.take(), other_tail.take()) {
+            (Some(first), Some(last)) => (first, last),
+            _ => return,
+        };
+        let moved = mem::take(other_len);
+
+        let (head, tail, len) = self.ends();
+        Self::set_prev(first, prev);
+        Self::set_next(last, next);
+        match prev {
+            Some(prev) => Self::set_next(prev, Some(first)),
+            None => *head = Some(first),
+        }
+        match next {
+            Some(next) => Self::set_prev(next, Some(last)),
+            None => *tail = Some(last),
+        }
+
+        *len += moved;
+    }
+
+    // Move every node before node at index to other, which must be empty
+    // SAFETY: node must be linked into this list at index
+    unsafe fn split_before_node(
+        &mut self,
+        node: NonNull<Self::Node>,
+        index: usize,
+        other: &mut Self,
+    ) {
+        // We have this:
+        //
+        //     list.front -> A <-> B <-> C <-> D <- list.back
+        //                               ^
+        //                              node
+        //
+        //
+        // And we want to produce this:
+        //
+        //     list.front -> C <-> D <- list.back
+        //
+        //    other.front -> A <-> B <- other.back
+        //
+        // Nothing to move if node is the front
+        if let Some(prev) = Self::prev(node) {
+            Self::set_prev(node, None);
+            Self::set_next(prev, None);
+
+            let (head, _, len) = self.ends();
+            let (other_head, other_tail, other_len) = other.ends();
+            *other_head = head.replace(node);
+            *other_tail = Some(prev);
+            *other_len = index;
+            *len -= index;
+        }
+    }
+
+    // Move every node after node at index to other, which must be empty
+    // SAFETY: node must be linked into this list at index
+    unsafe fn split_after_node(
+        &mut self,
+        node: NonNull<Self::Node>,
+        index: usize,
+        other: &mut Self,
+    ) {
+        // We have this:
+        //
+        //     list.front -> A <-> B <-> C <-> D <- list.back
+        //                         ^
+        //                        node
+        //
+        //
+        // And we want to produce this:
+        //
+        //     list.front -> A <-> B <- list.back
+        //
+        //    other.front -> C <-> D <- other.back
+        //
+        // Nothing to move if node is the back
+        if let Some(next) = Self::next(node) {
+            Self::set_next(node, None);
+            Self::set_prev(next, None);
+
+            let (_, tail, len) = self.ends();
+            let (other_head, other_tail, other_len) = other.ends();
+            *other_head = Some(next);
+            *other_tail = tail.replace(node);
+            *other_len = *len - index - 1;
+            *len = index + 1;
+        }
+    }
+}
+
+// Every cursor here sees the list as a ring with a ghost node between back and front:
+//
+//     ghost -> front <-> ... <-> back -> ghost
+//
+// At ghost node cur and index are None, so no index is ever out of range
+
+// Node after cur, next of ghost node is the front
+// SAFETY: cur must be linked into the list front belongs to
+pub(super) unsafe fn next_of<L: RawList>(
+    cur: Option<NonNull<L::Node>>,
+    front: Option<NonNull<L::Node>>,
+) -> Option<NonNull<L::Node>> {
+    match cur {
+        Some(cur) => L::next(cur),
+        None => front,
+    }
+}
+
+// Node before cur, prev of ghost node is the back
+// SAFETY: cur must be linked into the list back belongs to
+pub(super) unsafe fn prev_of<L: RawList>(
+    cur: Option<NonNull<L::Node>>,
+    back: Option<NonNull<L::Node>>,
+) -> Option<NonNull<L::Node>> {
+    match cur {
+        Some(cur) => L::prev(cur),
+        None => back,
+    }
+}
+
+// SAFETY: cur must be linked into the list front belongs to
+pub(super) unsafe fn move_next<L: RawList>(
+    cur: &mut Option<NonNull<L::Node>>,
+    index: &mut Option<usize>,
+    front: Option<NonNull<L::Node>>,
+) {
+    *cur = next_of::<L>(*cur, front);
+    *index = match (*cur, *index) {
+        // Moving past the back lands on ghost node
+        (None, _) => None,
+        (Some(_), Some(index)) => Some(index + 1),
+        (Some(_), None) => Some(0),
+    };
+}
+
+// SAFETY: cur must be linked into the list back belongs to, which is len long
+pub(super) unsafe fn move_prev<L: RawList>(
+    cur: &mut Option<NonNull<L::Node>>,
+    index: &mut Option<usize>,
+    back: Option<NonNull<L::Node>>,
+    len: usize,
+) {
+    *cur = prev_of::<L>(*cur, back);
+    *index = match (*cur, *index) {
+        // Moving past the front lands on ghost node
+        (None, _) => None,
+        (Some(_), Some(index)) => Some(index - 1),
+        (Some(_), None) => Some(len - 1),
+    };
+}
@@ -1,3 +1,5 @@
+mod treiber; // Lock-free, shared between threads
+
 #[derive(Debug)]
 struct Node<T> {
     val: T,
@@ -125,6 +127,27 @@ impl<T> OkStack<T> {
     }
 }
 
+// Serialized as a sequence from top to bottom, same order as iter()
+#[cfg(feature = "serde")]
+impl<T: serde::Serialize> serde::Serialize for OkStack<T> {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(self.iter())
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de, T: serde::Deserialize<'de>> serde::Deserialize<'de> for OkStack<T> {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        // First element is the top, so push from the bottom up
+        let vals = <Vec<T> as serde::Deserialize>::deserialize(deserializer)?;
+        let mut stack = OkStack::new();
+        for val in vals.into_iter().rev() {
+            stack.push(val);
+        }
+        Ok(stack)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -166,4 +189,30 @@ mod tests {
         assert_eq!(iter.next(), Some(1));
         assert_eq!(iter.next(), None);
     }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_serde() {
+        use crate::test_serializer::{from_value, to_value, Value};
+
+        let mut stack = OkStack::new();
+        stack.push(1);
+        stack.push(2);
+        stack.push(3);
+        let value = to_value(&stack).unwrap();
+        assert_eq!(
+            value,
+            Value::Seq(vec![Value::Int(3), Value::Int(2), Value::Int(1)])
+        );
+
+        let mut stack: OkStack<i32> = from_value(value).unwrap();
+        assert_eq!(stack.pop(), Some(3));
+        assert_eq!(stack.pop(), Some(2));
+        assert_eq!(stack.pop(), Some(1));
+        assert_eq!(stack.pop(), None);
+
+        let empty: OkStack<i32> = from_value(Value::Seq(vec![])).unwrap();
+        assert_eq!(empty.peek(), None);
+        assert!(from_value::<OkStack<i32>>(Value::Int(1)).is_err());
+    }
 }
@@ -0,0 +1,252 @@
+use crate::hazard::{self, HazardPointer};
+use std::marker::PhantomData;
+use std::mem::ManuallyDrop;
+use std::ptr;
+use std::sync::atomic::{AtomicPtr, Ordering};
+
+// Lock-free version of OkStack (Treiber stack), push and pop only need &self
+// head is swapped with compare-and-swap, so threads never block each other
+//
+//     head -> A -> B -> C -> null
+//
+// Popped node may still be read by other poppers (its next), so it is retired through
+// hazard pointers instead of freed right away, which also keeps ABA away:
+// a node can't be freed and pushed again at the same address while someone still looks at it
+
+struct Node<T> {
+    // Moved out by the popper which wins the CAS, so node never drops it
+    val: ManuallyDrop<T>,
+    // Never changes after node is pushed
+    next: *mut Node<T>,
+}
+
+pub struct TreiberStack<T> {
+    head: AtomicPtr<Node<T>>,
+    // Stack owns its values
+    _phantom: PhantomData<T>,
+}
+
+impl<T> TreiberStack<T> {
+    pub const fn new() -> Self {
+        Self {
+            head: AtomicPtr::new(ptr::null_mut()),
+            _phantom: PhantomData,
+        }
+    }
+
+    pub fn push(&self, val: T) {
+        let node = Box::into_raw(Box::new(Node {
+            val: ManuallyDrop::new(val),
+            next: ptr::null_mut(),
+        }));
+
+        let mut head = self.head.load(Ordering::Relaxed);
+        loop {
+            // Node is not shared yet, so plain write is fine
+            unsafe { (*node).next = head };
+            // Release: whoever sees node as head also sees its val and next
+            match self
+                .head
+                .compare_exchange_weak(head, node, Ordering::Release, Ordering::Relaxed)
+            {
+                Ok(_) => return,
+                Err(new_head) => head = new_head,
+            }
+        }
+    }
+
+    pub fn pop(&self) -> Option<T> {
+        let hazard = HazardPointer::new();
+        loop {
+            let head = hazard.protect(&self.head);
+            if head.is_null() {
+                return None;
+            }
+
+            // head can't be freed while protected, so reading next is fine even if it's popped by now
+            let next = unsafe { (*head).next };
+            if self
+                .head
+                .compare_exchange(head, next, Ordering::Acquire, Ordering::Relaxed)
+                .is_ok()
+            {
+                hazard.reset();
+                unsafe {
+                    // We won the CAS, so value is ours, only node memory is left for other readers
+                    let val = ptr::read(&(*head).val);
+                    hazard::retire(head);
+                    return Some(ManuallyDrop::into_inner(val));
+                }
+            }
+        }
+    }
+
+    // Can't hand out &T like OkStack::peek, a popper may move the value out and drop it meanwhile
+    // Copying bits of a Copy value is fine though, nobody writes to it after push
+    pub fn peek_copied(&self) -> Option<T>
+    where
+        T: Copy,
+    {
+        let hazard = HazardPointer::new();
+        let head = hazard.protect(&self.head);
+        unsafe { head.as_ref() }.map(|node| *node.val)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.head.load(Ordering::Acquire).is_null()
+    }
+}
+
+impl<T> Default for TreiberStack<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> Drop for TreiberStack<T> {
+    fn drop(&mut self) {
+        // &mut self, nobody else can touch the nodes anymore, free them right away
+        let mut cur = *self.head.get_mut();
+        while !cur.is_null() {
+            let mut node = unsafe { Box::from_raw(cur) };
+            cur = node.next;
+            unsafe { ManuallyDrop::drop(&mut node.val) };
+        }
+    }
+}
+
+// Values move between threads through the stack, they are never shared
+unsafe impl<T: Send> Send for TreiberStack<T> {}
+unsafe impl<T: Send> Sync for TreiberStack<T> {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::AtomicUsize;
+    use std::sync::Arc;
+    use std::thread;
+
+    #[test]
+    fn test() {
+        let stack = TreiberStack::new();
+        assert!(stack.is_empty());
+        assert_eq!(stack.pop(), None);
+        assert_eq!(stack.peek_copied(), None);
+
+        stack.push(1);
+        stack.push(2);
+        stack.push(3);
+        assert!(!stack.is_empty());
+        assert_eq!(stack.peek_copied(), Some(3));
+        assert_eq!(stack.pop(), Some(3));
+        assert_eq!(stack.pop(), Some(2));
+        stack.push(4);
+        assert_eq!(stack.pop(), Some(4));
+        assert_eq!(stack.pop(), Some(1));
+        assert_eq!(stack.pop(), None);
+        assert!(stack.is_empty());
+    }
+
+    #[test]
+    fn test_stress() {
+        const THREADS: usize = 4;
+        const PER_THREAD: usize = 10_000;
+
+        let stack = TreiberStack::new();
+        let received: Vec<Vec<usize>> = thread::scope(|s| {
+            for t in 0..THREADS {
+                let stack = &stack;
+                s.spawn(move || {
+                    for i in 0..PER_THREAD {
+                        stack.push(t * PER_THREAD + i);
+                    }
+                });
+            }
+
+            let consumers: Vec<_> = (0..THREADS)
+                .map(|_| {
+                    let stack = &stack;
+                    s.spawn(move || {
+                        let mut got = Vec::new();
+                        while got.len() < PER_THREAD {
+                            match stack.pop() {
+                                Some(val) => got.push(val),
+                                None => thread::yield_now(),
+                            }
+                        }
+                        got
+                    })
+                })
+                .collect();
+            consumers.into_iter().map(|h| h.join().unwrap()).collect()
+        });
+
+        // Every value popped exactly once
+        let mut all: Vec<_> = received.into_iter().flatten().collect();
+        all.sort_unstable();
+        assert_eq!(all, (0..THREADS * PER_THREAD).collect::<Vec<_>>());
+        assert!(stack.is_empty());
+    }
+
+    #[test]
+    fn test_stress_push_pop_peek() {
+        // Same thread pushes then pops, so popped nodes get reused by the allocator right away,
+        // which is where ABA would show up without hazard pointers
+        let stack = TreiberStack::new();
+        let pushed = AtomicUsize::new(0);
+        let popped = AtomicUsize::new(0);
+        thread::scope(|s| {
+            for t in 0..4 {
+                let (stack, pushed, popped) = (&stack, &pushed, &popped);
+                s.spawn(move || {
+                    for i in 0..20_000usize {
+                        stack.push((t, i));
+                        pushed.fetch_add(t * 100_000 + i, Ordering::Relaxed);
+                        if let Some((t, i)) = stack.pop() {
+                            popped.fetch_add(t * 100_000 + i, Ordering::Relaxed);
+                        }
+                        if let Some((t, i)) = stack.peek_copied() {
+                            assert!(t < 4 && i < 20_000);
+                        }
+                    }
+                });
+            }
+        });
+
+        while let Some((t, i)) = stack.pop() {
+            popped.fetch_add(t * 100_000 + i, Ordering::Relaxed);
+        }
+        assert_eq!(pushed.into_inner(), popped.into_inner());
+    }
+
+    #[test]
+    fn test_drop() {
+        struct Counted(Arc<AtomicUsize>);
+
+        impl Drop for Counted {
+            fn drop(&mut self) {
+                self.0.fetch_add(1, Ordering::Relaxed);
+            }
+        }
+
+        let drops = Arc::new(AtomicUsize::new(0));
+        let stack = TreiberStack::new();
+        thread::scope(|s| {
+            for _ in 0..4 {
+                s.spawn(|| {
+                    for _ in 0..100 {
+                        stack.push(Counted(drops.clone()));
+                    }
+                    for _ in 0..50 {
+                        drop(stack.pop());
+                    }
+                });
+            }
+        });
+        assert_eq!(drops.load(Ordering::Relaxed), 200);
+
+        // Remaining values are dropped with the stack, popped ones exactly once before
+        drop(stack);
+        assert_eq!(drops.load(Ordering::Relaxed), 400);
+    }
+}
@@ -0,0 +1,304 @@
+use crate::hazard::{self, HazardPointer};
+use std::marker::PhantomData;
+use std::mem::MaybeUninit;
+use std::ptr;
+use std::sync::atomic::{AtomicPtr, Ordering};
+
+// Lock-free version of third's queue (Michael-Scott queue), push and pop only need &self
+// head always points to a dummy node, first value lives in the node after it
+//
+//     head                tail
+//       |                   |
+//       v                   v
+//     dummy -> A -> B -> C -> null
+//
+// pop moves head one node forward, the node it lands on becomes the new dummy
+// push links after the last node first, then swings tail. tail may lag one node behind,
+// so anyone who sees that moves it forward before going on
+//
+// Unlinked dummies may still be read by other threads, so they're retired through hazard pointers
+
+struct Node<T> {
+    // Uninit in the dummy, moved out by the popper that makes this node the dummy
+    val: MaybeUninit<T>,
+    next: AtomicPtr<Node<T>>,
+}
+
+impl<T> Node<T> {
+    fn new(val: MaybeUninit<T>) -> *mut Self {
+        Box::into_raw(Box::new(Node {
+            val,
+            next: AtomicPtr::new(ptr::null_mut()),
+        }))
+    }
+}
+
+pub struct MsQueue<T> {
+    head: AtomicPtr<Node<T>>,
+    tail: AtomicPtr<Node<T>>,
+    // Queue owns its values
+    _phantom: PhantomData<T>,
+}
+
+impl<T> MsQueue<T> {
+    pub fn new() -> Self {
+        let dummy = Node::new(MaybeUninit::uninit());
+        Self {
+            head: AtomicPtr::new(dummy),
+            tail: AtomicPtr::new(dummy),
+            _phantom: PhantomData,
+        }
+    }
+
+    pub fn push(&self, val: T) {
+        let node = Node::new(MaybeUninit::new(val));
+        let hazard = HazardPointer::new();
+        loop {
+            // Never null, there's always a dummy
+            let tail = hazard.protect(&self.tail);
+            let next = unsafe { (*tail).next.load(Ordering::Acquire) };
+
+            if !next.is_null() {
+                // tail is lagging, help the other pusher and retry
+                self.advance_tail(tail, next);
+                continue;
+            }
+
+            // Release: whoever reaches node through next also sees its val
+            if unsafe { &(*tail).next }
+                .compare_exchange(next, node, Ordering::Release, Ordering::Relaxed)
+                .is_ok()
+            {
+                self.advance_tail(tail, node);
+                return;
+            }
+        }
+    }
+
+    pub fn pop(&self) -> Option<T> {
+        let head_hazard = HazardPointer::new();
+        let next_hazard = HazardPointer::new();
+        loop {
+            let head = head_hazard.protect(&self.head);
+            // next is only freed after head moves past it, checked below
+            let next = next_hazard.protect(unsafe { &(*head).next });
+            if head != self.head.load(Ordering::Acquire) {
+                continue;
+            }
+            if next.is_null() {
+                return None;
+            }
+
+            // Never let head pass tail, or tail would point to a freed node
+            let tail = self.tail.load(Ordering::Acquire);
+            if head == tail {
+                self.advance_tail(tail, next);
+                continue;
+            }
+
+            if self
+                .head
+                .compare_exchange(head, next, Ordering::AcqRel, Ordering::Relaxed)
+                .is_ok()
+            {
+                unsafe {
+                    // next is the new dummy and its value is ours, but next itself
+                    // can be popped and retired meanwhile, so read it before dropping the hazard
+                    let val = (*next).val.assume_init_read();
+                    next_hazard.reset();
+                    head_hazard.reset();
+                    hazard::retire(head);
+                    return Some(val);
+                }
+            }
+        }
+    }
+
+    // Fine if this fails, someone else already moved tail forward
+    fn advance_tail(&self, tail: *mut Node<T>, next: *mut Node<T>) {
+        let _ = self
+            .tail
+            .compare_exchange(tail, next, Ordering::Release, Ordering::Relaxed);
+    }
+
+    pub fn is_empty(&self) -> bool {
+        let hazard = HazardPointer::new();
+        let head = hazard.protect(&self.head);
+        unsafe { (*head).next.load(Ordering::Acquire) }.is_null()
+    }
+}
+
+impl<T> Default for MsQueue<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> Drop for MsQueue<T> {
+    fn drop(&mut self) {
+        // &mut self, nobody else can touch the nodes anymore, free them right away
+        // First node is the dummy, its value is already gone
+        let dummy = unsafe { Box::from_raw(*self.head.get_mut()) };
+        let mut cur = dummy.next.load(Ordering::Relaxed);
+        while !cur.is_null() {
+            let mut node = unsafe { Box::from_raw(cur) };
+            cur = *node.next.get_mut();
+            unsafe { node.val.assume_init_drop() };
+        }
+    }
+}
+
+// Values move between threads through the queue, they are never shared
+unsafe impl<T: Send> Send for MsQueue<T> {}
+unsafe impl<T: Send> Sync for MsQueue<T> {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::AtomicUsize;
+    use std::sync::Arc;
+    use std::thread;
+
+    #[test]
+    fn test() {
+        let queue = MsQueue::new();
+        assert!(queue.is_empty());
+        assert_eq!(queue.pop(), None);
+
+        queue.push(1);
+        queue.push(2);
+        queue.push(3);
+        assert!(!queue.is_empty());
+        assert_eq!(queue.pop(), Some(1));
+        assert_eq!(queue.pop(), Some(2));
+
+        queue.push(4);
+        queue.push(5);
+        assert_eq!(queue.pop(), Some(3));
+        assert_eq!(queue.pop(), Some(4));
+        assert_eq!(queue.pop(), Some(5));
+        assert_eq!(queue.pop(), None);
+        assert!(queue.is_empty());
+
+        // Works again after it was emptied
+        queue.push(6);
+        assert_eq!(queue.pop(), Some(6));
+        assert_eq!(queue.pop(), None);
+    }
+
+    #[test]
+    fn test_stress() {
+        const PRODUCERS: usize = 4;
+        const CONSUMERS: usize = 4;
+        const PER_PRODUCER: usize = 10_000;
+
+        let queue = MsQueue::new();
+        let popped = AtomicUsize::new(0);
+        let received: Vec<Vec<(usize, usize)>> = thread::scope(|s| {
+            for p in 0..PRODUCERS {
+                let queue = &queue;
+                s.spawn(move || {
+                    for i in 0..PER_PRODUCER {
+                        queue.push((p, i));
+                    }
+                });
+            }
+
+            let consumers: Vec<_> = (0..CONSUMERS)
+                .map(|_| {
+                    let (queue, popped) = (&queue, &popped);
+                    s.spawn(move || {
+                        let mut got = Vec::new();
+                        while popped.load(Ordering::Relaxed) < PRODUCERS * PER_PRODUCER {
+                            match queue.pop() {
+                                Some(val) => {
+                                    got.push(val);
+                                    popped.fetch_add(1, Ordering::Relaxed);
+                                }
+                                None => thread::yield_now(),
+                            }
+                        }
+                        got
+                    })
+                })
+                .collect();
+            consumers.into_iter().map(|h| h.join().unwrap()).collect()
+        });
+
+        // FIFO: each consumer sees every producer's values in the order they were pushed
+        for got in &received {
+            let mut last = [None; PRODUCERS];
+            for &(p, i) in got {
+                assert!(last[p] < Some(i), "producer {p}: {i} after {:?}", last[p]);
+                last[p] = Some(i);
+            }
+        }
+
+        // Every value popped exactly once
+        let mut all: Vec<_> = received.into_iter().flatten().collect();
+        all.sort_unstable();
+        let expected: Vec<_> = (0..PRODUCERS)
+            .flat_map(|p| (0..PER_PRODUCER).map(move |i| (p, i)))
+            .collect();
+        assert_eq!(all, expected);
+        assert!(queue.is_empty());
+    }
+
+    #[test]
+    fn test_stress_push_pop() {
+        // Every thread pushes and pops at the same time, so head keeps catching up with tail
+        let queue = MsQueue::new();
+        let pushed = AtomicUsize::new(0);
+        let popped = AtomicUsize::new(0);
+        thread::scope(|s| {
+            for t in 0..4 {
+                let (queue, pushed, popped) = (&queue, &pushed, &popped);
+                s.spawn(move || {
+                    for i in 0..20_000usize {
+                        queue.push(t * 100_000 + i);
+                        pushed.fetch_add(t * 100_000 + i, Ordering::Relaxed);
+                        if let Some(val) = queue.pop() {
+                            popped.fetch_add(val, Ordering::Relaxed);
+                        }
+                    }
+                });
+            }
+        });
+
+        while let Some(val) = queue.pop() {
+            popped.fetch_add(val, Ordering::Relaxed);
+        }
+        assert_eq!(pushed.into_inner(), popped.into_inner());
+    }
+
+    #[test]
+    fn test_drop() {
+        struct Counted(Arc<AtomicUsize>);
+
+        impl Drop for Counted {
+            fn drop(&mut self) {
+                self.0.fetch_add(1, Ordering::Relaxed);
+            }
+        }
+
+        let drops = Arc::new(AtomicUsize::new(0));
+        let queue = MsQueue::new();
+        thread::scope(|s| {
+            for _ in 0..4 {
+                s.spawn(|| {
+                    for _ in 0..100 {
+                        queue.push(Counted(drops.clone()));
+                    }
+                    for _ in 0..50 {
+                        drop(queue.pop());
+                    }
+                });
+            }
+        });
+        assert_eq!(drops.load(Ordering::Relaxed), 200);
+
+        // Remaining values are dropped with the queue, the dummy has none to drop
+        drop(queue);
+        assert_eq!(drops.load(Ordering::Relaxed), 400);
+    }
+}
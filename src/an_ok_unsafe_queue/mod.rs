@@ -2,3 +2,4 @@ mod first; // Safe but failed
 mod second; // Unsafe
 mod stack_borrowed;
 mod third;
+mod fourth; // Lock-free, shared between threads
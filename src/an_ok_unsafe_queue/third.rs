@@ -154,6 +154,26 @@ impl<T> List<T> {
     }
 }
 
+// Serialized as a sequence from head (next to pop) to tail
+#[cfg(feature = "serde")]
+impl<T: serde::Serialize> serde::Serialize for List<T> {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(self.iter())
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de, T: serde::Deserialize<'de>> serde::Deserialize<'de> for List<T> {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let vals = <Vec<T> as serde::Deserialize>::deserialize(deserializer)?;
+        let mut list = List::new();
+        for val in vals {
+            list.push(val);
+        }
+        Ok(list)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -175,4 +195,25 @@ mod tests {
         assert_eq!(list.pop(), Some(13));
         assert_eq!(list.pop(), None);
     }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_serde() {
+        use crate::test_serializer::{from_value, to_value, Value};
+
+        let mut list = List::new();
+        list.push(String::from("a"));
+        list.push(String::from("b"));
+        let value = to_value(&list).unwrap();
+        assert_eq!(
+            value,
+            Value::Seq(vec![Value::Str("a".into()), Value::Str("b".into())])
+        );
+
+        let mut list: List<String> = from_value(value).unwrap();
+        assert_eq!(list.tail().map(String::as_str), Some("b"));
+        assert_eq!(list.pop().as_deref(), Some("a"));
+        assert_eq!(list.pop().as_deref(), Some("b"));
+        assert_eq!(list.pop(), None);
+    }
 }
@@ -0,0 +1,344 @@
+use std::cell::RefCell;
+use std::mem;
+use std::ptr;
+use std::sync::atomic::{fence, AtomicBool, AtomicPtr, Ordering};
+
+// Hazard pointers, memory reclamation for the lock-free lists
+// Before dereferencing a shared node, a thread publishes its address in a hazard slot
+// A removed node is retired instead of freed, and only freed once no slot holds its address
+// -> no use-after-free, and a node can't be freed and reused under a reader's feet (no ABA)
+//
+//     SLOTS -> [ptr | active] -> [ptr | active] -> [ptr | active] -> null
+//
+// Slots are never freed, only recycled, so any thread can walk them at any time
+
+struct Slot {
+    ptr: AtomicPtr<u8>,
+    active: AtomicBool,
+    // Never changes once the slot is published
+    next: *const Slot,
+}
+
+// Slot fields are atomics, next is immutable after publish
+unsafe impl Sync for Slot {}
+
+static SLOTS: AtomicPtr<Slot> = AtomicPtr::new(ptr::null_mut());
+
+// Retired nodes left behind by exited threads, adopted by the next scan
+static ORPHANS: AtomicPtr<Orphans> = AtomicPtr::new(ptr::null_mut());
+
+// Scan once a thread has this many retired nodes
+const SCAN_THRESHOLD: usize = 64;
+
+pub struct HazardPointer {
+    slot: &'static Slot,
+}
+
+impl HazardPointer {
+    pub fn new() -> Self {
+        // Reuse an inactive slot first
+        let mut cur = SLOTS.load(Ordering::Acquire);
+        while let Some(slot) = unsafe { cur.as_ref() } {
+            if !slot.active.load(Ordering::Relaxed)
+                && slot
+                    .active
+                    .compare_exchange(false, true, Ordering::Acquire, Ordering::Relaxed)
+                    .is_ok()
+            {
+                return Self { slot };
+            }
+            cur = slot.next as *mut Slot;
+        }
+
+        // Every slot is busy, publish a new one at the front
+        let slot = Box::leak(Box::new(Slot {
+            ptr: AtomicPtr::new(ptr::null_mut()),
+            active: AtomicBool::new(true),
+            next: ptr::null(),
+        }));
+        let mut head = SLOTS.load(Ordering::Acquire);
+        loop {
+            slot.next = head;
+            match SLOTS.compare_exchange_weak(head, slot, Ordering::AcqRel, Ordering::Acquire) {
+                Ok(_) => return Self { slot },
+                Err(new_head) => head = new_head,
+            }
+        }
+    }
+
+    // Load src and publish it, returned pointer can be dereferenced until reset or drop
+    // (as long as the structure only frees its nodes through retire)
+    pub fn protect<T>(&self, src: &AtomicPtr<T>) -> *mut T {
+        let mut ptr = src.load(Ordering::Relaxed);
+        loop {
+            self.slot.ptr.store(ptr as *mut u8, Ordering::Relaxed);
+            // Pairs with fence in scan: either scan sees our slot,
+            // or we see the node was already unlinked and try again
+            fence(Ordering::SeqCst);
+            let current = src.load(Ordering::Acquire);
+            if current == ptr {
+                return ptr;
+            }
+            ptr = current;
+        }
+    }
+
+    pub fn reset(&self) {
+        self.slot.ptr.store(ptr::null_mut(), Ordering::Release);
+    }
+}
+
+impl Default for HazardPointer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Drop for HazardPointer {
+    fn drop(&mut self) {
+        self.reset();
+        self.slot.active.store(false, Ordering::Release);
+    }
+}
+
+#[derive(Clone, Copy)]
+struct Retired {
+    ptr: *mut u8,
+    free: unsafe fn(*mut u8),
+}
+
+unsafe fn free_box<T>(ptr: *mut u8) {
+    drop(Box::from_raw(ptr as *mut T));
+}
+
+struct Orphans {
+    retired: Vec<Retired>,
+    next: *mut Orphans,
+}
+
+// Per thread retired nodes, handed over as orphans when the thread exits
+struct RetiredList(Vec<Retired>);
+
+impl Drop for RetiredList {
+    fn drop(&mut self) {
+        scan(&mut self.0);
+        push_orphans(mem::take(&mut self.0));
+    }
+}
+
+thread_local! {
+    static RETIRED: RefCell<RetiredList> = const { RefCell::new(RetiredList(Vec::new())) };
+}
+
+/// Free `ptr` (as a `Box<T>`) once no hazard pointer protects it
+///
+/// # Safety
+///
+/// `ptr` must come from `Box::into_raw` and be unlinked from the shared structure,
+/// so no thread can start protecting it anymore. Drop of `T` runs on whichever thread frees it,
+/// at some later point, so it must not rely on borrowed data (lists retire nodes whose value is
+/// already moved out). It may retire other nodes or call `reclaim` itself
+pub unsafe fn retire<T>(ptr: *mut T) {
+    let retired = Retired {
+        ptr: ptr as *mut u8,
+        free: free_box::<T>,
+    };
+
+    let pushed = RETIRED.try_with(|list| {
+        let len = {
+            let mut list = list.borrow_mut();
+            list.0.push(retired);
+            list.0.len()
+        };
+        if len >= SCAN_THRESHOLD {
+            scan_local(list);
+        }
+    });
+    // Thread local is gone while the thread is shutting down, leave it to another thread
+    if pushed.is_err() {
+        push_orphans(vec![retired]);
+    }
+}
+
+// Free every retired node of this thread (and orphans) which is not protected right now
+pub fn reclaim() {
+    let _ = RETIRED.try_with(scan_local);
+}
+
+// Scan with the list moved out of its RefCell, freeing runs Drop of the nodes,
+// which may retire more nodes (or reclaim) and borrow the list again
+fn scan_local(list: &RefCell<RetiredList>) {
+    let mut retired = mem::take(&mut list.borrow_mut().0);
+    scan(&mut retired);
+    // Whatever got retired meanwhile is already in there, still protected nodes join it
+    list.borrow_mut().0.append(&mut retired);
+}
+
+fn push_orphans(retired: Vec<Retired>) {
+    if retired.is_empty() {
+        return;
+    }
+
+    let orphans = Box::into_raw(Box::new(Orphans {
+        retired,
+        next: ptr::null_mut(),
+    }));
+    let mut head = ORPHANS.load(Ordering::Relaxed);
+    loop {
+        unsafe { (*orphans).next = head };
+        match ORPHANS.compare_exchange_weak(head, orphans, Ordering::Release, Ordering::Relaxed) {
+            Ok(_) => return,
+            Err(new_head) => head = new_head,
+        }
+    }
+}
+
+fn scan(retired: &mut Vec<Retired>) {
+    // Adopt whatever exited threads left behind
+    let mut orphans = ORPHANS.swap(ptr::null_mut(), Ordering::Acquire);
+    while !orphans.is_null() {
+        let batch = unsafe { Box::from_raw(orphans) };
+        orphans = batch.next;
+        retired.extend(batch.retired);
+    }
+
+    if retired.is_empty() {
+        return;
+    }
+
+    // Pairs with fence in protect
+    fence(Ordering::SeqCst);
+    let mut hazards = Vec::new();
+    let mut cur = SLOTS.load(Ordering::Acquire);
+    while let Some(slot) = unsafe { cur.as_ref() } {
+        let ptr = slot.ptr.load(Ordering::Acquire);
+        if !ptr.is_null() {
+            hazards.push(ptr);
+        }
+        cur = slot.next as *mut Slot;
+    }
+    hazards.sort_unstable();
+
+    retired.retain(|node| {
+        let protected = hazards.binary_search(&node.ptr).is_ok();
+        if !protected {
+            unsafe { (node.free)(node.ptr) };
+        }
+        protected
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::AtomicUsize;
+    use std::sync::Arc;
+    use std::thread;
+
+    struct Flag(Arc<AtomicUsize>);
+
+    impl Drop for Flag {
+        fn drop(&mut self) {
+            self.0.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    #[test]
+    fn test_protected_is_not_freed() {
+        let freed = Arc::new(AtomicUsize::new(0));
+        let src = AtomicPtr::new(Box::into_raw(Box::new(Flag(freed.clone()))));
+
+        let hazard = HazardPointer::new();
+        let node = hazard.protect(&src);
+        src.store(ptr::null_mut(), Ordering::Release);
+        unsafe { retire(node) };
+
+        reclaim();
+        assert_eq!(freed.load(Ordering::Relaxed), 0);
+        // Still safe to read
+        assert_eq!(unsafe { &(*node).0 }.load(Ordering::Relaxed), 0);
+
+        drop(hazard);
+        reclaim();
+        assert_eq!(freed.load(Ordering::Relaxed), 1);
+    }
+
+    fn slot_count() -> usize {
+        let mut count = 0;
+        let mut cur = SLOTS.load(Ordering::Acquire) as *const Slot;
+        while let Some(slot) = unsafe { cur.as_ref() } {
+            count += 1;
+            cur = slot.next;
+        }
+        count
+    }
+
+    #[test]
+    fn test_slots_are_recycled() {
+        let before = slot_count();
+        for _ in 0..1000 {
+            let first = HazardPointer::new();
+            let second = HazardPointer::new();
+            drop(first);
+            drop(second);
+        }
+        // Other tests may take a few slots in parallel, but not one per iteration
+        assert!(slot_count() < before + 64);
+    }
+
+    #[test]
+    fn test_retire_from_drop() {
+        // Freeing a node retires the next one, from inside scan
+        struct Chain {
+            next: *mut Chain,
+            freed: Arc<AtomicUsize>,
+        }
+
+        impl Drop for Chain {
+            fn drop(&mut self) {
+                self.freed.fetch_add(1, Ordering::Relaxed);
+                if !self.next.is_null() {
+                    unsafe { retire(self.next) };
+                    reclaim();
+                }
+            }
+        }
+
+        let freed = Arc::new(AtomicUsize::new(0));
+        let count = 2 * SCAN_THRESHOLD;
+        let mut next = ptr::null_mut();
+        for _ in 0..count {
+            next = Box::into_raw(Box::new(Chain {
+                next,
+                freed: freed.clone(),
+            }));
+        }
+        unsafe { retire(next) };
+        reclaim();
+        assert_eq!(freed.load(Ordering::Relaxed), count);
+    }
+
+    #[test]
+    fn test_orphans_are_adopted() {
+        let freed = Arc::new(AtomicUsize::new(0));
+        let hazard = HazardPointer::new();
+        let src = AtomicPtr::new(Box::into_raw(Box::new(Flag(freed.clone()))));
+        let node = hazard.protect(&src);
+
+        // Retiring thread exits while node is still protected
+        // (raw pointers are not Send, so the address crosses over as usize)
+        let addr = node as usize;
+        thread::scope(|s| {
+            s.spawn(move || unsafe { retire(addr as *mut Flag) });
+        });
+        assert_eq!(freed.load(Ordering::Relaxed), 0);
+
+        drop(hazard);
+        // Orphans may be adopted by any thread, keep scanning until someone freed it
+        while freed.load(Ordering::Relaxed) == 0 {
+            reclaim();
+            thread::yield_now();
+        }
+        assert_eq!(freed.load(Ordering::Relaxed), 1);
+    }
+}
@@ -1,6 +1,9 @@
 mod a_bad_safe_deque;
 mod a_bad_stack;
 mod a_persistent_stack;
-mod a_production_unsafe_deque;
+pub mod a_production_unsafe_deque;
 mod an_ok_stack;
 mod an_ok_unsafe_queue;
+mod hazard;
+#[cfg(all(test, feature = "serde"))]
+mod test_serializer;